@@ -4,21 +4,48 @@
 //! increasing integer index. A new tree has always a `Root` node. A [`Node`] is a
 //! represntation of a rust construct obtained by parsing the input code.
 //!
+//! The id of a node is a stable handle: ids are never reused and do not change
+//! when other nodes are inserted or removed. The id of a removed node refers
+//! to no node.
+//!
 
-use super::construct::{make_construct_form_tokens, Construct};
+use super::construct::{
+    macro_semi, make_child_construct, make_construct_like, Construct, ConstructKind,
+};
+use super::error::{Error, Result};
+use super::import::Import;
 use super::node::Node;
-use proc_macro2::Span;
+use super::select::Selector;
+use super::traverse::{Ancestors, Bfs, Events, Postorder, Preorder};
+use super::utils::{free_variables, pat_bindings};
+use super::visit::{TreeVisitor, TreeVisitorMut};
+use proc_macro2::{Span, TokenStream};
 use quote::quote;
+#[cfg(feature = "serialize")]
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::ops::Deref;
-use syn::{Ident, ImplItemFn, ItemFn, ItemImpl, ItemStruct, ItemTrait, Macro, Stmt, TraitItemFn};
+use syn::punctuated::Punctuated;
+use syn::visit::{self, Visit};
+use syn::visit_mut::{self, VisitMut};
+use syn::{
+    Arm, Attribute, Block, Expr, ExprBlock, ExprIf, ExprMethodCall, Ident, ImplItem, ImplItemFn,
+    Item, ItemConst, ItemEnum, ItemFn, ItemImpl, ItemMod, ItemStatic, ItemStruct, ItemTrait,
+    ItemType, ItemUnion, ItemUse, Macro, Stmt, TraitItem, TraitItemFn,
+};
 
 #[cfg_attr(feature = "serialize", derive(Deserialize, Serialize))]
 /// The Syntax Tree struct
 pub struct SyntaxTree {
-    nodes: Vec<Node>,
-    last_id: usize,
+    nodes: Vec<Option<Node>>,
     current_node_id: usize,
     current_level: usize,
+    /// The inner attributes of the parsed file, e.g. `#![no_std]`.
+    #[cfg_attr(
+        feature = "serialize",
+        serde(with = "crate::serialize::attrs", default)
+    )]
+    attrs: Vec<Attribute>,
 }
 
 impl SyntaxTree {
@@ -28,13 +55,24 @@ impl SyntaxTree {
         let ident = Ident::new("root", Span::call_site());
         let node = Node::new(root, 0, ident);
         Self {
-            nodes: vec![node],
-            last_id: 0,
+            nodes: vec![Some(node)],
             current_node_id: 0,
             current_level: 1,
+            attrs: vec![],
         }
     }
 
+    // sets the inner attributes of the parsed file
+    pub(crate) fn set_attributes(&mut self, attrs: Vec<Attribute>) {
+        self.attrs = attrs;
+    }
+
+    /// Returns the inner attributes of the parsed file (e.g. `#![no_std]` or
+    /// a `//!` doc comment), which come before the children of the root.
+    pub fn get_attributes(&self) -> &[Attribute] {
+        &self.attrs
+    }
+
     // Not public api. Sets the level from the parse_inner macro.
     pub(crate) fn set_current_level(&mut self, level: usize) {
         self.current_level = level;
@@ -42,13 +80,7 @@ impl SyntaxTree {
 
     /// Returns a mutable reference to the current node.
     fn get_current_node_mut(&mut self) -> Option<&mut Node> {
-        self.nodes.get_mut(self.current_node_id)
-    }
-
-    // increases the id of the nodes in the given slice by 1
-    // needed to maintain the ordering of the indexes
-    fn update_ids(&mut self, id: usize) {
-        self.nodes[id..].iter_mut().for_each(|node| node.id += 1);
+        self.get_node_mut(self.current_node_id)
     }
 
     /// Adds a node to the SyntaxTree given its data
@@ -65,141 +97,883 @@ impl SyntaxTree {
             node.level = 0;
         };
 
-        self.nodes.push(node);
-        self.last_id = id;
+        self.nodes.push(Some(node));
         id
     }
 
     /// Returns the node with the given name (identifier) or `None`
     /// if the node is not in the tree
     pub fn find_node(&self, ident: &Ident) -> Option<&Node> {
-        self.nodes.iter().find(|node| node.ident.eq(ident))
+        self.iter().find(|node| node.ident.eq(ident))
     }
 
     /// Returns the node with the given name (identifier) or `None`
     /// if the node is not in the tree
     pub fn find_node_mut(&mut self, ident: &Ident) -> Option<&mut Node> {
-        self.nodes.iter_mut().find(|node| node.ident.eq(ident))
+        self.iter_mut().find(|node| node.ident.eq(ident))
     }
 
-    /// Adds a node to the tree before the node with the
-    /// specified `id`. The added node has the same level of
-    /// the node with the specified id and its `data` field is
-    /// equal to the input data
-    ///     
-    /// Arguments:
-    ///
-    /// * `ident` - name (identifier) of the node after which the new node is added
-    /// * `data`  - data of the new node that is being adde
-    pub fn insert_node_before(&mut self, ident: &Ident, data: Construct) -> Option<usize> {
-        let Some(sibling) = self.find_node(&ident) else {
-            return None;
-        };
-        let node_id = sibling.id - 1;
-        let mut node = Node::new(data, node_id, ident.to_owned());
+    /// Returns an iterator over the nodes with the given name (identifier)
+    /// in source order
+    pub fn find_all<'a>(&'a self, ident: &'a Ident) -> impl Iterator<Item = &'a Node> + 'a {
+        self.find_where(move |node| node.ident.eq(ident))
+    }
 
-        node.level = sibling.level;
-        node.parent = sibling.parent;
+    /// Returns an iterator over the nodes of the given kind in source order
+    pub fn find_by_kind(&self, kind: ConstructKind) -> impl Iterator<Item = &Node> {
+        self.find_where(move |node| node.get_kind() == kind)
+    }
 
-        self.nodes.insert(node_id, node);
-        self.update_ids(node_id + 1);
+    /// Returns an iterator over the nodes for which the predicate is `true`
+    /// in source order
+    pub fn find_where<P>(&self, predicate: P) -> impl Iterator<Item = &Node>
+    where
+        P: FnMut(&&Node) -> bool,
+    {
+        self.preorder().filter(predicate)
+    }
 
-        Some(node_id)
+    /// Returns an iterator over the descendants of the node with the given id
+    /// for which the predicate is `true` in source order
+    pub fn find_in<P>(&self, id: usize, predicate: P) -> impl Iterator<Item = &Node>
+    where
+        P: FnMut(&&Node) -> bool,
+    {
+        self.descendants(id).filter(predicate)
     }
 
-    /// Adds a node to the tree after the node with the
-    /// specified `id`. The added node has the same level of
-    /// the node with the specified id and its `data` field is
-    /// equal to the input data
+    /// Returns the variables that the closure or `async` block with the given
+    /// id captures from its enclosing function, in order of first use.
     ///
-    /// Arguments:
+    /// The captured variables are the free variables of the closure that are
+    /// bound by a `let` statement in scope before the closure, the parameters
+    /// of the function are not included. Returns an empty vector if the node
+    /// is not a closure or an `async` block.
+    pub fn captures(&self, id: usize) -> Vec<Ident> {
+        let expr = match self.build_construct(id) {
+            Some(construct @ (Construct::ExprClosure(_) | Construct::ExprAsync(_))) => {
+                construct.into_expr()
+            }
+            _ => None,
+        };
+        let Some(expr) = expr else {
+            return vec![];
+        };
+
+        // the `let` statements in scope precede the closure or one of its
+        // ancestors in the same block
+        let mut locals = HashSet::new();
+        let mut current = id;
+        for parent in self.ancestors(id) {
+            for sibling in self
+                .children(parent.id)
+                .take_while(|node| node.id != current)
+            {
+                if let Construct::Stmt(Stmt::Local(local)) | Construct::Local(local) = &sibling.data
+                {
+                    locals.extend(pat_bindings(&local.pat));
+                }
+            }
+            if matches!(
+                parent.data,
+                Construct::ItemFn(_) | Construct::ImplItemFn(_) | Construct::TraitItemFn(_)
+            ) {
+                break;
+            }
+            current = parent.id;
+        }
+
+        let free = free_variables(&expr).into_iter();
+        free.filter(|ident| locals.contains(ident)).collect()
+    }
+
+    /// Returns the ids of the nodes whose values the function with the given
+    /// id returns, in source order: the `return` expressions in its body and
+    /// its tail expression.
     ///
-    /// * `ident` - name (identifier) of the node after which the new node is added
-    /// * `data`  - data of the new node that is being adde
-    pub fn insert_node_after(&mut self, ident: &Ident, data: Construct) -> Option<usize> {
-        let Some(sibling) = self.find_node(&ident) else {
-            return None;
+    /// The `return` expressions of nested closures, `async` blocks and items
+    /// are not included. Returns an empty vector if the node is not found.
+    pub fn return_points(&self, id: usize) -> Vec<usize> {
+        let Some(node) = self.get_node(id) else {
+            return vec![];
         };
-        let node_id = sibling.id + 1;
-        let mut node = Node::new(data, node_id, ident.to_owned());
 
-        node.level = sibling.level;
-        node.parent = sibling.parent;
+        let mut points = vec![];
+        let mut stack = node.children.iter().rev().copied().collect::<Vec<usize>>();
+        while let Some(current) = stack.pop() {
+            let Some(node) = self.get_node(current) else {
+                continue;
+            };
+            if node.parent == id && node.tail || node.get_kind() == ConstructKind::ExprReturn {
+                points.push(current);
+            }
+            let nested = match &node.data {
+                Construct::ExprClosure(_) | Construct::ExprAsync(_) => true,
+                Construct::Stmt(_) | Construct::Local(_) | Construct::StmtMacro(_) => false,
+                Construct::Block(_) | Construct::Arm(_) | Construct::Method(_) => false,
+                construct => !construct.is_expr(),
+            };
+            if !nested {
+                stack.extend(node.children.iter().rev());
+            }
+        }
+        points
+    }
 
-        self.nodes.insert(node_id, node);
-        self.update_ids(node_id + 1);
+    /// Returns the number of nodes of each kind of construct in the tree
+    pub fn count_kinds(&self) -> BTreeMap<ConstructKind, usize> {
+        let mut counts = BTreeMap::new();
+        for node in self.iter() {
+            *counts.entry(node.get_kind()).or_insert(0) += 1;
+        }
+        counts
+    }
 
-        Some(node_id)
+    /// Returns the innermost node covering the given position in the source
+    /// code, with a 1-indexed line and a 0-indexed column, or `None` if no
+    /// node covers it. Positions are only known when the tree is parsed
+    /// from source text, e.g. with [`parse_str`](crate::parse_str).
+    pub fn node_at(&self, line: usize, column: usize) -> Option<&Node> {
+        self.iter()
+            .filter(|node| !node.is_root() && node.contains(line, column))
+            .max_by_key(|node| node.level)
     }
 
-    /// Adds a node at the end of the tree and returns the id
-    /// of the node
-    pub fn append_node(&mut self, data: Construct, name: &str) -> Option<usize> {
-        let id = self.last_id + 1;
-        let parent_node = self.get_node_mut(self.last_id)?;
-        let ident = Ident::new(name, Span::call_site());
-        let mut node = Node::new(data, id, ident);
+    /// Returns the ids of the nodes matched by the selector in source order,
+    /// e.g. `tree.select("impl[trait=Operations] > fn[name=square]")`. See
+    /// [`select`](crate::select) for the syntax of the selectors.
+    pub fn select(&self, selector: &str) -> Result<Vec<usize>> {
+        let selector = Selector::parse(selector)?;
+        let nodes = self
+            .preorder()
+            .filter(|node| selector.matches(self, node.id));
+        Ok(nodes.map(|node| node.id).collect())
+    }
+
+    /// Returns the path of the module the node with the given id is
+    /// declared in, e.g. `crate::a::b`, or `None` if the node is not in the tree
+    pub fn module_path(&self, id: usize) -> Option<String> {
+        let mut node = self.get_node(id)?;
+        let mut modules = vec![];
+        while !node.is_root() {
+            node = self.get_node(node.parent)?;
+            if let Construct::ItemMod(item) = &node.data {
+                modules.push(item.ident.to_string());
+            }
+        }
+
+        modules.push("crate".to_string());
+        modules.reverse();
+        Some(modules.join("::"))
+    }
+
+    /// Returns the fully qualified path of the node with the given id,
+    /// e.g. `crate::a::b::foo`, or `None` if the node is not in the tree
+    pub fn item_path(&self, id: usize) -> Option<String> {
+        let node = self.get_node(id)?;
+        if node.is_root() {
+            return Some("crate".to_string());
+        }
+        Some(format!("{}::{}", self.module_path(id)?, node.ident))
+    }
+
+    /// Returns the item with the given fully qualified path, e.g.
+    /// `crate::a::b::foo`, or `None` if there is no such item. Only items
+    /// declared directly in a module are considered.
+    pub fn find_path(&self, path: &str) -> Option<&Node> {
+        self.iter().find(|node| {
+            let in_module = self.get_node(node.parent).is_some_and(|parent| {
+                matches!(parent.data, Construct::Root | Construct::ItemMod(_))
+            });
+            in_module && !node.is_root() && self.item_path(node.id).as_deref() == Some(path)
+        })
+    }
+
+    /// Returns the imports visible in the scope of the node with the given
+    /// id, innermost scope first. The imports are collected from the `use`
+    /// declarations in the node itself and in its ancestors, up to the
+    /// enclosing module, since a module does not see the imports of its parent.
+    pub fn imports_in_scope(&self, id: usize) -> Vec<Import> {
+        let mut imports = vec![];
+        let mut scope = self.get_node(id);
+
+        while let Some(node) = scope {
+            let uses = node.children.iter().filter_map(|&id| self.get_node(id));
+            imports.extend(uses.flat_map(Node::get_imports));
+
+            if node.is_root() || matches!(node.data, Construct::ItemMod(_)) {
+                break;
+            }
+            scope = self.get_node(node.parent);
+        }
+
+        imports
+    }
+
+    /// Returns `true` if the item with the given path, e.g. `"std::io::Write"`,
+    /// is imported in the scope of the node with the given id
+    pub fn is_imported(&self, id: usize, path: &str) -> bool {
+        self.imports_in_scope(id)
+            .iter()
+            .any(|import| import.imports(path))
+    }
 
-        parent_node.children.push(id);
-        node.parent = parent_node.id;
-        node.level = parent_node.level - 1;
+    /// Adds a node for the construct before the node with the given id,
+    /// among the children of its parent, and returns the id of the new node.
+    /// The nested constructs of the construct (e.g. the statements of a
+    /// function) become children of the new node.
+    pub fn insert_node_before(&mut self, id: usize, data: Construct) -> Result<usize> {
+        self.insert_sibling(id, data, 0)
+    }
+
+    /// Adds a node for the construct after the node with the given id,
+    /// among the children of its parent, and returns the id of the new node.
+    /// The nested constructs of the construct (e.g. the statements of a
+    /// function) become children of the new node.
+    pub fn insert_node_after(&mut self, id: usize, data: Construct) -> Result<usize> {
+        self.insert_sibling(id, data, 1)
+    }
 
-        self.nodes.push(node);
+    // adds a node among the children of the parent of the node with the
+    // given id, `offset` positions after that node
+    fn insert_sibling(&mut self, id: usize, data: Construct, offset: usize) -> Result<usize> {
+        let sibling = self
+            .get_node(id)
+            .filter(|node| !node.is_root())
+            .ok_or(Error::NodeNotFound { id })?;
+        let parent = sibling.parent;
+        let siblings = &self
+            .get_node(parent)
+            .ok_or(Error::NodeNotFound { id: parent })?
+            .children;
+        let index = siblings
+            .iter()
+            .position(|&child| child == id)
+            .ok_or(Error::NodeNotFound { id })?;
+        self.insert_construct(parent, index + offset, &data)
+    }
+
+    /// Adds a node as the last top level construct of the tree and returns
+    /// the id of the node
+    pub fn append_node(&mut self, data: Construct, name: &str) -> Option<usize> {
+        let index = self.get_node(0)?.children.len();
+        let id = self.insert_construct(0, index, &data).ok()?;
+        self.get_node_mut(id)?.ident = Ident::new(name, Span::call_site());
         Some(id)
     }
 
-    /// Adds a node at the end of the tree and returns its id
-    /// given the TokenStream representation of the node
+    /// Adds a node as the last top level construct of the tree and returns
+    /// its id given the TokenStream representation of the node
     pub fn append_tokenstream(
         &mut self,
         tokens: proc_macro2::TokenStream,
         name: &str,
-    ) -> Option<usize> {
-        let data = make_construct_form_tokens(tokens).unwrap();
+    ) -> Result<usize> {
+        let id = self.push_child(0, tokens)?;
+        if let Some(node) = self.get_node_mut(id) {
+            node.ident = Ident::new(name, Span::call_site());
+        }
+        Ok(id)
+    }
 
-        let id = self.last_id + 1;
-        let parent_node = self.get_node_mut(self.last_id)?;
-        let ident = Ident::new(name, Span::call_site());
-        let mut node = Node::new(data, id, ident);
+    /// Removes the node with the given id together with all its descendants
+    /// and returns them as a new tree, where the removed node is the only
+    /// child of the root. Returns `None` for the root, an unknown id and the
+    /// receiver of a chain of method calls, which the chain cannot do without.
+    pub fn remove_subtree(&mut self, id: usize) -> Option<SyntaxTree> {
+        let node = self.get_node(id)?;
+        if node.is_root() || self.is_receiver(node) {
+            return None;
+        }
 
-        parent_node.children.push(id);
-        node.parent = parent_node.id;
-        node.level = id;
+        let parent = node.parent;
+        let removed = self.subtree_ids(id);
+        let mut subtree = SyntaxTree::new();
+        let offset = node.level - 1;
+        let ids = removed
+            .iter()
+            .enumerate()
+            .map(|(index, &old)| (old, index + 1))
+            .collect::<HashMap<usize, usize>>();
 
-        self.nodes.push(node);
-        Some(id)
+        for &old in &removed {
+            let mut node = self.nodes[old].take()?;
+            node.id = ids[&old];
+            node.parent = ids.get(&node.parent).copied().unwrap_or(0);
+            node.children = node.children.iter().map(|child| ids[child]).collect();
+            node.level -= offset;
+            subtree.nodes.push(Some(node));
+        }
+        subtree.get_node_mut(0)?.children.push(1);
+
+        self.get_node_mut(parent)?
+            .children
+            .retain(|&child| child != id);
+        self.update_stmts(parent);
+        self.reset_cursors(parent);
+        Some(subtree)
+    }
+
+    /// Removes the node with the given id and returns it. The children of
+    /// the node take its place among the children of its parent and move one
+    /// level up.
+    ///
+    /// Returns an error for the root or an unknown id, and when a child cannot
+    /// be a child of the parent, e.g. the methods of an `impl` block or the
+    /// statements of a function at the top level. Use
+    /// [`remove_subtree`](SyntaxTree::remove_subtree) to remove those together
+    /// with the node. The receiver of a chain of method calls cannot be
+    /// removed.
+    pub fn remove_node(&mut self, id: usize) -> Result<Node> {
+        let node = self.get_node(id).ok_or(Error::NodeNotFound { id })?;
+        if node.is_root() {
+            return Err(Error::NodeNotFound { id });
+        }
+        let parent = node.parent;
+        if self.is_receiver(node) {
+            return Err(Error::InvalidChild { id, parent });
+        }
+        let parent_node = self
+            .get_node(parent)
+            .ok_or(Error::NodeNotFound { id: parent })?;
+        let invalid = node.children.iter().find(|&&child| {
+            let child = self.get_node(child);
+            child.is_some_and(|child| !parent_node.data.can_contain(&child.data))
+        });
+        if let Some(&child) = invalid {
+            return Err(Error::InvalidChild { id: child, parent });
+        }
+
+        let mut removed = node.clone();
+        let children = std::mem::take(&mut removed.children);
+        for descendant in self.subtree_ids(id).split_off(1) {
+            if let Some(node) = self.get_node_mut(descendant) {
+                node.level -= 1;
+            }
+        }
+        for &child in &children {
+            if let Some(node) = self.get_node_mut(child) {
+                node.parent = parent;
+            }
+        }
+
+        let siblings = &mut self
+            .get_node_mut(parent)
+            .ok_or(Error::NodeNotFound { id: parent })?
+            .children;
+        if let Some(index) = siblings.iter().position(|&child| child == id) {
+            siblings.splice(index..=index, children);
+        }
+        self.nodes[id] = None;
+        self.update_stmts(parent);
+        self.reset_cursors(parent);
+        Ok(removed)
+    }
+
+    /// Replaces the construct of the node with the given id with the
+    /// construct parsed from the tokens and rebuilds the children of the
+    /// node from the new construct. Returns the id of the node.
+    ///
+    /// The tokens are parsed as the same sort of construct as the one they
+    /// replace, e.g. a method of an `impl` block or a statement of a function.
+    pub fn replace_node(&mut self, id: usize, tokens: TokenStream) -> Result<usize> {
+        let node = self.get_node(id).ok_or(Error::NodeNotFound { id })?;
+        if node.is_root() {
+            return Err(Error::NodeNotFound { id });
+        }
+
+        let construct = make_construct_like(tokens, &node.data)?;
+        let parent = node.parent;
+        let mut subtree = SyntaxTree::new();
+        subtree.set_current_level(node.level);
+        subtree.visit_construct(&construct);
+
+        for descendant in self.subtree_ids(id).split_off(1) {
+            self.nodes[descendant] = None;
+        }
+        self.reset_cursors(id);
+
+        // the first node of the subtree takes the place of the replaced node
+        let Some(top) = self.graft(subtree, parent) else {
+            return Ok(id);
+        };
+        let Some(top) = self.nodes[top].take() else {
+            return Ok(id);
+        };
+        for &child in &top.children {
+            if let Some(child) = self.get_node_mut(child) {
+                child.parent = id;
+            }
+        }
+
+        let node = self.get_node_mut(id).ok_or(Error::NodeNotFound { id })?;
+        // a branch keeps its name, which tells the `then` and `else` apart
+        if !matches!(top.data, Construct::Block(_)) {
+            node.ident = top.ident;
+        }
+        node.data = top.data;
+        node.semi = top.semi;
+        node.semi_added = false;
+        node.span = top.span;
+        node.children = top.children;
+        self.update_stmts(parent);
+        Ok(id)
+    }
+
+    // whether the node is the receiver of a chain of method calls, i.e. the
+    // child of the chain that is not one of its calls
+    fn is_receiver(&self, node: &Node) -> bool {
+        let parent = self.get_node(node.parent);
+        parent.is_some_and(|parent| matches!(parent.data, Construct::ExprMethodCall(_)))
+            && !matches!(node.data, Construct::Method(_))
+    }
+
+    // returns the ids of the node with the given id and of all its
+    // descendants in source order
+    fn subtree_ids(&self, id: usize) -> Vec<usize> {
+        let descendants = self.descendants(id).map(|node| node.id);
+        std::iter::once(id).chain(descendants).collect()
     }
 
-    /// Returns a proc_macro2::TokenStream from the nodes of the tree
+    // moves the internal cursors to the given node when the node they point
+    // to has been removed
+    fn reset_cursors(&mut self, fallback: usize) {
+        if self.get_node(self.current_node_id).is_none() {
+            self.current_node_id = fallback;
+        }
+    }
+
+    // adds the nodes of the subtree at the end of the tree, the children of
+    // the root of the subtree become children of the given parent. Returns
+    // the id of the first child of the root of the subtree.
+    fn graft(&mut self, subtree: SyntaxTree, parent: usize) -> Option<usize> {
+        let base = self.nodes.len() - 1;
+        let new_id = |old: usize| if old == 0 { parent } else { base + old };
+        let top = subtree.get_node(0)?.children.first().copied().map(new_id);
+
+        for mut node in subtree.nodes.into_iter().skip(1).flatten() {
+            node.id = new_id(node.id);
+            node.parent = new_id(node.parent);
+            node.children = node.children.into_iter().map(new_id).collect();
+            self.nodes.push(Some(node));
+        }
+        top
+    }
+
+    /// Parses the tokens into a construct and adds it as the child of the
+    /// node with the given id at the given position among its children.
+    /// Returns the id of the new node.
+    ///
+    /// The tokens are parsed as the sort of construct the parent can contain,
+    /// e.g. a statement for a function or a method for an `impl` block.
+    pub fn insert_child(
+        &mut self,
+        parent: usize,
+        index: usize,
+        tokens: TokenStream,
+    ) -> Result<usize> {
+        let node = self
+            .get_node(parent)
+            .ok_or(Error::NodeNotFound { id: parent })?;
+        if index > node.children.len() {
+            return Err(Error::InvalidIndex { id: parent, index });
+        }
+
+        let construct = make_child_construct(tokens, &node.data)?;
+        self.insert_construct(parent, index, &construct)
+    }
+
+    // adds the nodes of the construct and of its nested constructs, the top
+    // node is the child of the given parent at the given position among its
+    // children. Returns the id of the top node.
+    fn insert_construct(
+        &mut self,
+        parent: usize,
+        index: usize,
+        construct: &Construct,
+    ) -> Result<usize> {
+        let node = self
+            .get_node(parent)
+            .ok_or(Error::NodeNotFound { id: parent })?;
+        if index > node.children.len() {
+            return Err(Error::InvalidIndex { id: parent, index });
+        }
+
+        let mut subtree = SyntaxTree::new();
+        subtree.set_current_level(node.level + 1);
+        subtree.visit_construct(construct);
+
+        let id = self
+            .graft(subtree, parent)
+            .ok_or(Error::NodeNotFound { id: parent })?;
+        let node = self
+            .get_node_mut(parent)
+            .ok_or(Error::NodeNotFound { id: parent })?;
+        node.children.insert(index, id);
+        self.update_stmts(parent);
+        Ok(id)
+    }
+
+    // adds a semicolon to the statements among the children of the node with
+    // the given id that need one because another statement follows them, and
+    // marks the last statement as the tail expression if it has no semicolon.
+    // The last statement loses a semicolon that was added this way.
+    fn update_stmts(&mut self, id: usize) {
+        let Some(node) = self.get_node(id).filter(|node| node.data.has_stmts()) else {
+            return;
+        };
+        let mut followed = node.children[self.head_len(node)..].to_owned();
+        let last = followed.pop();
+        for child in followed {
+            let Some(child) = self.get_node_mut(child) else {
+                continue;
+            };
+            child.tail = false;
+            if child.data.needs_semi() && !child.semi {
+                child.semi = true;
+                child.semi_added = true;
+            } else if let Construct::StmtMacro(mac) = &mut child.data {
+                if mac.semi_token.is_none() {
+                    mac.semi_token = macro_semi(&mac.mac);
+                    child.semi_added = mac.semi_token.is_some();
+                }
+            }
+        }
+        if let Some(child) = last.and_then(|last| self.get_node_mut(last)) {
+            if std::mem::take(&mut child.semi_added) {
+                child.semi = false;
+                if let Construct::StmtMacro(mac) = &mut child.data {
+                    mac.semi_token = None;
+                }
+            }
+            child.tail = child.data.is_expr() && !child.semi;
+        }
+    }
+
+    /// Parses the tokens into a construct and adds it as the last child of
+    /// the node with the given id. Returns the id of the new node.
+    pub fn push_child(&mut self, parent: usize, tokens: TokenStream) -> Result<usize> {
+        let node = self
+            .get_node(parent)
+            .ok_or(Error::NodeNotFound { id: parent })?;
+        let index = node.children.len();
+        self.insert_child(parent, index, tokens)
+    }
+
+    /// Returns a proc_macro2::TokenStream from the nodes of the tree.
+    ///
+    /// Only the top level constructs are emitted. The body of every construct
+    /// is rebuilt from its children, so that modifications to nested nodes
+    /// (including inserted or removed nodes) are part of the output.
     pub fn get_tokenstream(&self) -> proc_macro2::TokenStream {
-        let nodes = &self.nodes;
-        quote! {#(#nodes)*}
+        let Some(root) = self.get_node(0) else {
+            return quote! {};
+        };
+
+        let constructs = root.children.iter().filter_map(|&id| {
+            let node = self.get_node(id)?;
+            self.build_construct(id)?.into_stmt(node.semi)
+        });
+        let attrs = &self.attrs;
+        quote! {#(#attrs)* #(#constructs)*}
+    }
+
+    /// Returns the construct of the node with the given id where the body
+    /// of the construct is rebuilt from the children of the node.
+    pub fn build_construct(&self, id: usize) -> Option<Construct> {
+        let node = self.get_node(id)?;
+        let mut construct = node.data.clone();
+
+        match &mut construct {
+            Construct::ItemFn(item) => item.block.stmts = self.build_stmts(node),
+            Construct::ImplItemFn(item) => item.block.stmts = self.build_stmts(node),
+            Construct::TraitItemFn(item) => {
+                if let Some(block) = &mut item.default {
+                    block.stmts = self.build_stmts(node);
+                }
+            }
+            Construct::ItemImpl(item) => {
+                let fns = self
+                    .build_children(node)
+                    .filter_map(|(_, child)| child.into_impl_item());
+                build_in_place(&mut item.items, |item| matches!(item, ImplItem::Fn(_)), fns);
+            }
+            Construct::ItemTrait(item) => {
+                let fns = self
+                    .build_children(node)
+                    .filter_map(|(_, child)| child.into_trait_item());
+                build_in_place(
+                    &mut item.items,
+                    |item| matches!(item, TraitItem::Fn(_)),
+                    fns,
+                );
+            }
+            Construct::ItemEnum(item) => {
+                let variants = self
+                    .build_children(node)
+                    .filter_map(|(_, child)| child.into_variant());
+                item.variants = build_punctuated(&item.variants, variants);
+            }
+            Construct::ItemUnion(item) => {
+                let fields = self
+                    .build_children(node)
+                    .filter_map(|(_, child)| child.into_field());
+                item.fields.named = build_punctuated(&item.fields.named, fields);
+            }
+            Construct::ItemMod(item) => {
+                if let Some((_, items)) = &mut item.content {
+                    *items = self
+                        .build_children(node)
+                        .filter_map(|(_, child)| child.into_item())
+                        .collect();
+                }
+            }
+            Construct::Stmt(Stmt::Local(local)) => {
+                if let Some(init) = &mut local.init {
+                    match init.expr.as_mut() {
+                        Expr::Block(block) => block.block.stmts = self.build_stmts(node),
+                        Expr::If(expr) => self.build_if(node, expr),
+                        Expr::Match(expr) => expr.arms = self.build_arms(node),
+                        Expr::Loop(expr) => expr.body.stmts = self.build_stmts(node),
+                        Expr::While(expr) => expr.body.stmts = self.build_stmts(node),
+                        Expr::ForLoop(expr) => expr.body.stmts = self.build_stmts(node),
+                        Expr::Unsafe(expr) => expr.block.stmts = self.build_stmts(node),
+                        Expr::Const(expr) => expr.block.stmts = self.build_stmts(node),
+                        Expr::TryBlock(expr) => expr.block.stmts = self.build_stmts(node),
+                        _ => (),
+                    }
+                    if !matches!(init.expr.as_ref(), Expr::Block(_)) {
+                        self.build_nested(node, nested_heads_mut(&mut init.expr));
+                    }
+                    // the `else` branch of a `let` with a refutable pattern
+                    if let Some((_, diverge)) = &mut init.diverge {
+                        if let Expr::Block(diverge) = diverge.as_mut() {
+                            diverge.block = self.build_diverge(node);
+                        }
+                    }
+                }
+            }
+            Construct::ExprIf(expr) => self.build_if(node, expr),
+            Construct::ExprMatch(expr) => expr.arms = self.build_arms(node),
+            Construct::ExprLoop(expr) => expr.body.stmts = self.build_stmts(node),
+            Construct::ExprWhile(expr) => expr.body.stmts = self.build_stmts(node),
+            Construct::ExprForLoop(expr) => expr.body.stmts = self.build_stmts(node),
+            Construct::ExprBlock(expr) => expr.block.stmts = self.build_stmts(node),
+            Construct::ExprUnsafe(expr) => expr.block.stmts = self.build_stmts(node),
+            Construct::ExprConst(expr) => expr.block.stmts = self.build_stmts(node),
+            Construct::ExprTryBlock(expr) => expr.block.stmts = self.build_stmts(node),
+            Construct::Block(block) => block.stmts = self.build_stmts(node),
+            Construct::Arm(arm) => {
+                self.build_body(node, &mut arm.body);
+                let guard = arm.guard.iter_mut().map(|(_, guard)| guard.as_mut());
+                self.build_nested(node, guard.collect());
+            }
+            Construct::ExprClosure(expr) => self.build_body(node, &mut expr.body),
+            Construct::ExprAsync(expr) => expr.block.stmts = self.build_stmts(node),
+            Construct::ExprMethodCall(expr) => {
+                if let Some(chain) = self.build_chain(node, expr) {
+                    construct = chain;
+                }
+            }
+            Construct::Method(method) => self.build_nested(node, method.args.iter_mut().collect()),
+            _ => (),
+        }
+
+        // the nested expressions of an expression are children of its node,
+        // a closure, `async` block or chain is rebuilt from its own children
+        let whole = matches!(
+            node.data,
+            Construct::ExprClosure(_) | Construct::ExprAsync(_) | Construct::ExprMethodCall(_)
+        );
+        if construct.is_expr() && !whole {
+            if let Some(mut expr) = construct.to_owned().into_expr() {
+                self.build_nested(node, nested_heads_mut(&mut expr));
+                construct = Construct::from_expr(expr).unwrap_or(construct);
+            }
+        }
+
+        Some(construct)
+    }
+
+    // rebuilds the constructs of the children of the given node
+    fn build_children<'a>(&'a self, node: &'a Node) -> impl Iterator<Item = (&'a Node, Construct)> {
+        node.children.iter().filter_map(|&id| {
+            let child = self.get_node(id)?;
+            Some((child, self.build_construct(id)?))
+        })
+    }
+
+    // rebuilds the statements of a block from the children of the given node
+    fn build_stmts(&self, node: &Node) -> Vec<Stmt> {
+        self.build_children(node)
+            .skip(self.head_len(node))
+            .filter_map(|(child, construct)| construct.into_stmt(child.semi))
+            .collect()
+    }
+
+    // the number of children of the node that are nested in the head of its
+    // construct and precede the statements of its body
+    fn head_len(&self, node: &Node) -> usize {
+        let mut nested = FindNested(vec![]);
+        for head in stmt_heads(node) {
+            nested.visit_expr(head);
+        }
+        let heads = node.children.iter().take(nested.0.len());
+        heads
+            .take_while(|&&child| {
+                self.get_node(child)
+                    .is_some_and(|child| is_nested(&child.data))
+            })
+            .count()
+    }
+
+    // rebuilds the branches of an `if` from the children of the given node:
+    // the `then` branch, and a block or a nested `if` for the `else` branch.
+    // An `if` before the `then` branch is nested in the condition.
+    fn build_if(&self, node: &Node, expr: &mut ExprIf) {
+        let mut then_branch = None;
+        let mut else_branch = None;
+        for (child, construct) in self.build_children(node) {
+            match construct {
+                Construct::Block(block) if child.ident == "then" => then_branch = Some(block),
+                _ if then_branch.is_none() => (),
+                Construct::Block(block) => {
+                    else_branch = Some(Expr::Block(ExprBlock {
+                        attrs: vec![],
+                        label: None,
+                        block,
+                    }))
+                }
+                Construct::ExprIf(else_if) => else_branch = Some(Expr::If(else_if)),
+                _ => (),
+            }
+        }
+
+        match then_branch {
+            Some(block) => expr.then_branch = block,
+            None => expr.then_branch.stmts.clear(),
+        }
+        let else_token = expr.else_branch.take().map(|(token, _)| token);
+        expr.else_branch =
+            else_branch.map(|branch| (else_token.unwrap_or_default(), Box::new(branch)));
+    }
+
+    // rebuilds the `else` branch of a `let` statement from the children of the
+    // given node
+    fn build_diverge(&self, node: &Node) -> Block {
+        let branch = self
+            .build_children(node)
+            .find_map(|(child, construct)| match construct {
+                Construct::Block(block) if child.ident == "else" => Some(block),
+                _ => None,
+            });
+        branch.unwrap_or_else(|| Block {
+            brace_token: Default::default(),
+            stmts: vec![],
+        })
+    }
+
+    // rebuilds the arms of a `match` from the children of the given node
+    fn build_arms(&self, node: &Node) -> Vec<Arm> {
+        self.build_children(node)
+            .filter_map(|(_, child)| child.into_arm())
+            .collect()
+    }
+
+    // rebuilds the body of a `match` arm or of a closure from the children of
+    // the given node, a body without braces gets braces if it has more than
+    // one statement
+    fn build_body(&self, node: &Node, body: &mut Expr) {
+        let mut stmts = self.build_stmts(node);
+        match body {
+            Expr::Block(block) if block.attrs.is_empty() && block.label.is_none() => {
+                block.block.stmts = stmts
+            }
+            body => match (stmts.pop(), stmts.is_empty()) {
+                (Some(Stmt::Expr(expr, None)), true) => *body = expr,
+                (last, _) => {
+                    stmts.extend(last);
+                    *body = Expr::Block(ExprBlock {
+                        attrs: vec![],
+                        label: None,
+                        block: Block {
+                            brace_token: Default::default(),
+                            stmts,
+                        },
+                    });
+                }
+            },
+        }
+    }
+
+    // replaces the nested expressions (e.g. closures, method calls or `if`
+    // expressions) of the given expressions with the ones rebuilt from the
+    // children of the given node, in source order
+    fn build_nested(&self, node: &Node, exprs: Vec<&mut Expr>) {
+        let nested = self
+            .build_children(node)
+            .filter(|(_, child)| is_nested(child))
+            .filter_map(|(_, child)| child.into_expr());
+        let mut nested = ReplaceNested(nested.collect::<Vec<Expr>>().into_iter());
+        for expr in exprs {
+            nested.visit_expr_mut(expr);
+        }
+    }
+
+    // rebuilds a chain of method calls from the children of the given node,
+    // the receiver of the first call followed by the calls in order. Returns
+    // the receiver if the chain has no calls left.
+    fn build_chain(&self, node: &Node, expr: &ExprMethodCall) -> Option<Construct> {
+        let mut receiver = None;
+        let mut methods = vec![];
+        for (_, child) in self.build_children(node) {
+            match child {
+                Construct::Method(method) => methods.push(method),
+                child if receiver.is_none() && methods.is_empty() => receiver = child.into_expr(),
+                _ => (),
+            }
+        }
+
+        // the receiver of the first call if no node comes before the calls
+        let mut chain = receiver.unwrap_or_else(|| {
+            let mut receiver = &expr.receiver;
+            while let Expr::MethodCall(call) = receiver.as_ref() {
+                receiver = &call.receiver;
+            }
+            receiver.as_ref().to_owned()
+        });
+        for mut method in methods {
+            method.receiver = Box::new(chain);
+            chain = Expr::MethodCall(method);
+        }
+        Construct::from_expr(chain)
     }
 
     /// Returns an SyntaxTree with the given capacity
     pub fn with_capacity(n: usize) -> Self {
         Self {
             nodes: Vec::with_capacity(n),
-            last_id: 0,
             current_node_id: 0,
             current_level: 0,
+            attrs: vec![],
         }
     }
 
-    /// Returns a reference to the node with the given id
+    /// Returns a reference to the node with the given id or `None` if
+    /// there is no such node, e.g. because it was removed
     pub fn get_node(&self, id: usize) -> Option<&Node> {
-        self.nodes.get(id)
+        self.nodes.get(id)?.as_ref()
     }
 
-    /// Returns a mutable reference to the node with the given id
+    /// Returns a mutable reference to the node with the given id or `None`
+    /// if there is no such node, e.g. because it was removed
     pub fn get_node_mut(&mut self, id: usize) -> Option<&mut Node> {
-        self.nodes.get_mut(id)
+        self.nodes.get_mut(id)?.as_mut()
     }
 
-    /// Returns the size of the SyntaxTree as the lenght of the vector
-    /// of nodes
+    /// Returns the number of nodes in the SyntaxTree
     pub fn length(&self) -> usize {
-        self.nodes.len()
+        self.iter().count()
     }
 
     /// Returns the capacity of the SyntaxTree as the capacity of the
@@ -208,27 +982,248 @@ impl SyntaxTree {
         self.nodes.capacity()
     }
 
-    /// Returns an iterator over the nodes of the SyntaxTree
+    /// Returns an iterator over the children of the node with the given id
+    /// in source order
+    pub fn children(&self, id: usize) -> impl Iterator<Item = &Node> {
+        let children = self.get_node(id).map(|node| node.children.as_slice());
+        let children = children.unwrap_or_default().iter();
+        children.filter_map(|&child| self.get_node(child))
+    }
+
+    /// Returns an iterator over the descendants of the node with the given id
+    /// in depth-first pre-order, without the node itself
+    pub fn descendants(&self, id: usize) -> Preorder<'_> {
+        let children = self.get_node(id).map(|node| node.children.as_slice());
+        Preorder::new(self, children.unwrap_or_default())
+    }
+
+    /// Returns an iterator over the ancestors of the node with the given id,
+    /// from its parent up to the root
+    pub fn ancestors(&self, id: usize) -> Ancestors<'_> {
+        Ancestors::new(self, id)
+    }
+
+    /// Returns an iterator over the other children of the parent of the node
+    /// with the given id in source order. The root has no siblings.
+    pub fn siblings(&self, id: usize) -> impl Iterator<Item = &Node> {
+        let parent = self.get_node(id).filter(|node| !node.is_root());
+        let siblings = parent
+            .into_iter()
+            .flat_map(|node| self.children(node.parent));
+        siblings.filter(move |node| node.id != id)
+    }
+
+    /// Returns an iterator over all the nodes of the tree in depth-first
+    /// pre-order, starting from the root
+    pub fn preorder(&self) -> Preorder<'_> {
+        Preorder::new(self, &[0])
+    }
+
+    /// Returns an iterator over all the nodes of the tree in depth-first
+    /// post-order, ending with the root
+    pub fn postorder(&self) -> Postorder<'_> {
+        Postorder::new(self, 0)
+    }
+
+    /// Returns an iterator over all the nodes of the tree in breadth-first
+    /// order, starting from the root
+    pub fn bfs(&self) -> Bfs<'_> {
+        Bfs::new(self, 0)
+    }
+
+    /// Returns an iterator over the events of a depth-first traversal of the
+    /// subtree of the node with the given id. A node is entered before its
+    /// descendants and left after them.
+    pub fn events(&self, id: usize) -> Events<'_> {
+        Events::new(self, id)
+    }
+
+    /// Visits the nodes of the tree in source order, starting from the root.
+    pub fn walk<'ast, V: TreeVisitor<'ast> + ?Sized>(&'ast self, visitor: &mut V) {
+        if let Some(root) = self.get_node(0) {
+            visitor.visit_node(self, root);
+        }
+    }
+
+    /// Visits the nodes of the tree in source order, starting from the root,
+    /// with a visitor that can modify the tree.
+    pub fn walk_mut<V: TreeVisitorMut + ?Sized>(&mut self, visitor: &mut V) {
+        visitor.visit_node_mut(self, 0);
+    }
+
+    /// Returns an iterator over the nodes of the SyntaxTree in the order
+    /// they were added. Use [`preorder`](Self::preorder) for source order.
     pub fn iter(&self) -> impl Iterator<Item = &Node> {
-        self.nodes.iter()
+        self.nodes.iter().flatten()
     }
 
     /// Returns a mutable iterator over the nodes of the SyntaxTree
     pub fn iter_mut(&mut self) -> impl Iterator<Item = &mut Node> {
-        self.nodes.iter_mut()
+        self.nodes.iter_mut().flatten()
     }
 
     #[cfg(feature = "debug")]
     pub fn print_tree(&self) {
         println!("level  id");
-        self.nodes.iter().for_each(|node| println!("{node:?}"));
-        println!("")
+        self.iter().for_each(|node| println!("{node:?}"));
+        println!()
+    }
+}
+
+// whether the construct is a closure, an `async` block, a chain of method
+// calls, a control flow expression or a block, which are nodes also when
+// nested in another expression
+fn is_nested(construct: &Construct) -> bool {
+    matches!(
+        construct,
+        Construct::ExprClosure(_)
+            | Construct::ExprAsync(_)
+            | Construct::ExprMethodCall(_)
+            | Construct::ExprIf(_)
+            | Construct::ExprMatch(_)
+            | Construct::ExprLoop(_)
+            | Construct::ExprWhile(_)
+            | Construct::ExprForLoop(_)
+            | Construct::ExprBlock(_)
+            | Construct::ExprUnsafe(_)
+            | Construct::ExprConst(_)
+            | Construct::ExprTryBlock(_)
+    )
+}
+
+// the expressions of the constructs for which `is_nested` is `true`
+fn is_nested_expr(expr: &Expr) -> bool {
+    matches!(
+        expr,
+        Expr::Closure(_)
+            | Expr::Async(_)
+            | Expr::MethodCall(_)
+            | Expr::If(_)
+            | Expr::Match(_)
+            | Expr::Loop(_)
+            | Expr::While(_)
+            | Expr::ForLoop(_)
+            | Expr::Block(_)
+            | Expr::Unsafe(_)
+            | Expr::Const(_)
+            | Expr::TryBlock(_)
+    )
+}
+
+// the parts of an expression whose nested expressions are children of its
+// node, the bodies of `if`, `match`, loops and blocks are children of their own
+fn nested_heads(expr: &Expr) -> Vec<&Expr> {
+    match expr {
+        Expr::If(expr) => vec![&expr.cond],
+        Expr::Match(expr) => vec![&expr.expr],
+        Expr::While(expr) => vec![&expr.cond],
+        Expr::ForLoop(expr) => vec![&expr.expr],
+        Expr::Loop(_) | Expr::Block(_) | Expr::Unsafe(_) | Expr::Const(_) | Expr::TryBlock(_) => {
+            vec![]
+        }
+        expr => vec![expr],
+    }
+}
+
+fn nested_heads_mut(expr: &mut Expr) -> Vec<&mut Expr> {
+    match expr {
+        Expr::If(expr) => vec![&mut expr.cond],
+        Expr::Match(expr) => vec![&mut expr.expr],
+        Expr::While(expr) => vec![&mut expr.cond],
+        Expr::ForLoop(expr) => vec![&mut expr.expr],
+        Expr::Loop(_) | Expr::Block(_) | Expr::Unsafe(_) | Expr::Const(_) | Expr::TryBlock(_) => {
+            vec![]
+        }
+        expr => vec![expr],
+    }
+}
+
+// the parts of the construct of the node whose nested expressions are
+// children of the node before the statements of its body, e.g. the condition
+// of a `while` loop or the guard of a `match` arm
+fn stmt_heads(node: &Node) -> Vec<&Expr> {
+    match &node.data {
+        Construct::ExprWhile(expr) => vec![&expr.cond],
+        Construct::ExprForLoop(expr) => vec![&expr.expr],
+        Construct::Arm(arm) => arm.guard.iter().map(|(_, guard)| guard.as_ref()).collect(),
+        Construct::Stmt(Stmt::Local(local)) => match local.init.as_ref() {
+            Some(init) if matches!(init.expr.as_ref(), Expr::While(_) | Expr::ForLoop(_)) => {
+                nested_heads(&init.expr)
+            }
+            _ => vec![],
+        },
+        _ => vec![],
+    }
+}
+
+// collects the closures, `async` blocks, chains of method calls, control
+// flow expressions and blocks that are not nested in another one
+struct FindNested<'ast>(Vec<&'ast Expr>);
+
+impl<'ast> Visit<'ast> for FindNested<'ast> {
+    fn visit_expr(&mut self, expr: &'ast Expr) {
+        if is_nested_expr(expr) {
+            self.0.push(expr);
+        } else {
+            visit::visit_expr(self, expr);
+        }
+    }
+}
+
+// replaces every expression found by `FindNested` with the next expression
+// of the iterator
+struct ReplaceNested<I>(I);
+
+impl<I: Iterator<Item = Expr>> VisitMut for ReplaceNested<I> {
+    fn visit_expr_mut(&mut self, expr: &mut Expr) {
+        if !is_nested_expr(expr) {
+            visit_mut::visit_expr_mut(self, expr);
+        } else if let Some(nested) = self.0.next() {
+            *expr = nested;
+        }
+    }
+}
+
+// replaces the items for which `rebuilt` is `true` with the rebuilt items in
+// order and keeps the other items in their place. Rebuilt items left over
+// follow the last replaced item, replaced items left over are removed.
+fn build_in_place<T>(
+    items: &mut Vec<T>,
+    rebuilt: impl Fn(&T) -> bool,
+    mut built: impl Iterator<Item = T>,
+) {
+    let last = items.iter().rposition(&rebuilt);
+    let mut result = Vec::with_capacity(items.len());
+    for (index, item) in std::mem::take(items).into_iter().enumerate() {
+        if rebuilt(&item) {
+            result.extend(built.next());
+        } else {
+            result.push(item);
+        }
+        if Some(index) == last {
+            result.extend(built.by_ref());
+        }
+    }
+    result.extend(built);
+    *items = result;
+}
+
+// collects the items into a punctuated sequence which keeps the trailing
+// punctuation of the original sequence
+fn build_punctuated<T, P: Default>(
+    original: &Punctuated<T, P>,
+    items: impl Iterator<Item = T>,
+) -> Punctuated<T, P> {
+    let mut punctuated = items.collect::<Punctuated<T, P>>();
+    if original.trailing_punct() && !punctuated.empty_or_trailing() {
+        punctuated.push_punct(P::default());
     }
+    punctuated
 }
 
 pub(crate) mod visitor {
     use crate::utils::{
-        match_expr, match_impl_item_fn, match_item_impl, match_lit_expr, match_pat, match_path,
+        match_arm, match_expr, match_impl_item_fn, match_item_ident, match_item_impl, match_path,
         match_trait_item_fn,
     };
 
@@ -246,6 +1241,10 @@ pub(crate) mod visitor {
             visit::visit_block(self, node);
             self.current_node_id = return_id;
             self.current_level -= 1;
+
+            if let Some(Stmt::Expr(_, None)) = node.stmts.last() {
+                self.mark_tail();
+            }
         }
 
         fn visit_item_fn(&mut self, i: &'ast ItemFn) {
@@ -259,90 +1258,62 @@ pub(crate) mod visitor {
         fn visit_stmt(&mut self, i: &'ast Stmt) {
             match i {
                 syn::Stmt::Local(local) => {
-                    let expr = local.init.as_ref();
-                    if let Some(exp) = expr {
-                        match exp.expr.deref() {
-                            syn::Expr::Block(block) => {
-                                let return_id = self.current_node_id;
+                    let expr = local.init.as_ref().map(|init| init.expr.deref());
+                    match expr {
+                        Some(syn::Expr::Block(block)) => {
+                            let return_id = self.current_node_id;
 
-                                let ident = Ident::new("Block", Span::call_site());
-                                let id = self.add_node(i.into(), ident);
+                            let ident = Ident::new("Block", Span::call_site());
+                            let id = self.add_node(i.into(), ident);
+                            self.current_node_id = id;
+                            self.visit_expr_block(block);
+                            self.current_node_id = return_id;
+                        }
+                        Some(expr) => {
+                            let ident = Ident::new("Local", Span::call_site());
+                            let id = self.add_node(i.into(), ident);
+                            self.visit_nested(id, nested_heads(expr));
+                            self.visit_branches(id, expr);
+
+                            let init = local.init.as_ref();
+                            let diverge = init.and_then(|init| init.diverge.as_ref());
+                            if let Some(syn::Expr::Block(block)) =
+                                diverge.map(|(_, expr)| expr.as_ref())
+                            {
+                                let return_id = self.current_node_id;
                                 self.current_node_id = id;
-                                self.visit_expr_block(block);
+                                self.current_level += 1;
+                                self.visit_branch(&block.block, "else");
+                                self.current_level -= 1;
                                 self.current_node_id = return_id;
                             }
-                            _ => {
-                                let ident = Ident::new("Local", Span::call_site());
-                                self.add_node(i.into(), ident); // TODO
-                            }
+                        }
+                        None => {
+                            let ident = Ident::new("Local", Span::call_site());
+                            self.add_node(i.into(), ident); // TODO
                         }
                     }
                 }
-                syn::Stmt::Item(item) => match item {
-                    syn::Item::Fn(func) => {
-                        self.visit_item_fn(func);
-                    }
-                    syn::Item::Impl(item_impl) => self.visit_item_impl(item_impl),
-                    &_ => {
-                        let ident = Ident::new("ItemImpl", Span::call_site());
-                        self.add_node(i.into(), ident);
-                    }
-                },
-                syn::Stmt::Expr(expr, _) => match expr {
-                    syn::Expr::Call(call) => {
-                        let ident = match_expr(&call.func);
-                        self.add_node(i.into(), ident);
-                    }
-                    syn::Expr::Path(path) => {
-                        let ident = match_path(&path.path);
-                        let ident = ident.first().unwrap();
-                        self.add_node(path.into(), ident.to_owned());
+                syn::Stmt::Item(item) => self.visit_item(item),
+                syn::Stmt::Expr(expr, semi) => {
+                    let id = self.visit_stmt_expr(i, expr);
+                    if let Some(node) = self.get_node_mut(id) {
+                        node.semi = semi.is_some();
                     }
-                    syn::Expr::Let(let_expr) => {
-                        let ident = match_pat(&let_expr.pat);
-                        let ident = ident.first().unwrap();
-                        self.add_node(let_expr.into(), ident.to_owned());
-                    }
-                    syn::Expr::Lit(lit_expr) => {
-                        let ident = match_lit_expr(&lit_expr.lit);
-                        self.add_node(lit_expr.into(), ident);
-                    }
-                    syn::Expr::Assign(assign) => {
-                        let ident = match_expr(&assign.left);
-                        self.add_node(assign.into(), ident);
-                    }
-                    syn::Expr::Macro(mac) => {
-                        let ident = if let Some(ident) = match_path(&mac.mac.path).first() {
-                            ident.to_owned()
-                        } else {
-                            Ident::new("macro", Span::call_site())
-                        };
-
-                        self.add_node(mac.into(), ident);
-                    }
-                    syn::Expr::Return(ret) => {
-                        let ident = if let Some(expr) = ret.expr.to_owned() {
-                            match_expr(&expr)
-                        } else {
-                            Ident::new("return", Span::call_site())
-                        };
-
-                        self.add_node(ret.into(), ident);
-                    }
-                    &_ => (), // TODO
-                },
+                }
                 syn::Stmt::Macro(mac) => {
-                    let ident = if let Some(ident) = match_path(&mac.mac.path).first() {
-                        self.add_node(mac.into(), ident.to_owned())
+                    if let Some(ident) = match_path(&mac.mac.path).first() {
+                        self.add_node(mac.into(), ident.to_owned());
                     } else {
                         let ident = Ident::new("macro", Span::call_site());
-                        self.add_node(mac.into(), ident)
+                        self.add_node(mac.into(), ident);
                     };
                 }
             };
         }
 
         fn visit_item_impl(&mut self, i: &'ast ItemImpl) {
+            let return_id = self.current_node_id;
             let id = if let Some(ident) = match_item_impl(i).first() {
                 self.add_node(i.into(), ident.to_owned())
             } else {
@@ -351,8 +1322,12 @@ pub(crate) mod visitor {
             };
 
             self.current_node_id = id;
-            visit::visit_item_impl(self, i);
-            self.current_node_id = 0;
+            for item in &i.items {
+                if let syn::ImplItem::Fn(func) = item {
+                    self.visit_impl_item_fn(func);
+                }
+            }
+            self.current_node_id = return_id;
         }
 
         fn visit_impl_item_fn(&mut self, i: &'ast ImplItemFn) {
@@ -363,7 +1338,7 @@ pub(crate) mod visitor {
             let id = self.add_node(i.into(), ident);
 
             self.current_node_id = id;
-            visit::visit_impl_item_fn(self, i);
+            self.visit_block(&i.block);
             self.current_node_id = return_id;
 
             self.current_level -= 1;
@@ -374,7 +1349,11 @@ pub(crate) mod visitor {
             let id = self.add_node(i.into(), i.ident.to_owned());
 
             self.current_node_id = id;
-            visit::visit_item_trait(self, i);
+            for item in &i.items {
+                if let syn::TraitItem::Fn(func) = item {
+                    self.visit_trait_item_fn(func);
+                }
+            }
             self.current_node_id = return_id;
         }
 
@@ -386,17 +1365,320 @@ pub(crate) mod visitor {
             let id = self.add_node(i.into(), ident);
 
             self.current_node_id = id;
-            visit::visit_trait_item_fn(self, i);
+            if let Some(block) = &i.default {
+                self.visit_block(block);
+            }
             self.current_node_id = return_id;
 
             self.current_level -= 1;
         }
 
+        fn visit_item(&mut self, i: &'ast Item) {
+            match i {
+                syn::Item::Fn(func) => self.visit_item_fn(func),
+                syn::Item::Impl(item_impl) => self.visit_item_impl(item_impl),
+                syn::Item::Struct(item_struct) => self.visit_item_struct(item_struct),
+                syn::Item::Trait(item_trait) => self.visit_item_trait(item_trait),
+                syn::Item::Enum(item_enum) => self.visit_item_enum(item_enum),
+                syn::Item::Union(item_union) => self.visit_item_union(item_union),
+                syn::Item::Const(item_const) => self.visit_item_const(item_const),
+                syn::Item::Static(item_static) => self.visit_item_static(item_static),
+                syn::Item::Type(item_type) => self.visit_item_type(item_type),
+                syn::Item::Mod(item_mod) => self.visit_item_mod(item_mod),
+                syn::Item::Use(item_use) => self.visit_item_use(item_use),
+                &_ => {
+                    let ident = match_item_ident(i);
+                    self.add_node(i.into(), ident);
+                }
+            }
+        }
+
+        fn visit_item_enum(&mut self, i: &'ast ItemEnum) {
+            let return_id = self.current_node_id;
+            let id = self.add_node(i.into(), i.ident.to_owned());
+
+            self.current_level += 1;
+            self.current_node_id = id;
+            for variant in &i.variants {
+                self.add_node(variant.into(), variant.ident.to_owned());
+            }
+            self.current_node_id = return_id;
+            self.current_level -= 1;
+        }
+
+        fn visit_item_union(&mut self, i: &'ast ItemUnion) {
+            let return_id = self.current_node_id;
+            let id = self.add_node(i.into(), i.ident.to_owned());
+
+            self.current_level += 1;
+            self.current_node_id = id;
+            for field in &i.fields.named {
+                let ident = field.ident.to_owned();
+                let ident = ident.unwrap_or_else(|| Ident::new("Field", Span::call_site()));
+                self.add_node(field.into(), ident);
+            }
+            self.current_node_id = return_id;
+            self.current_level -= 1;
+        }
+
+        fn visit_item_mod(&mut self, i: &'ast ItemMod) {
+            let return_id = self.current_node_id;
+            let id = self.add_node(i.into(), i.ident.to_owned());
+
+            self.current_level += 1;
+            self.current_node_id = id;
+            if let Some((_, items)) = &i.content {
+                for item in items {
+                    self.visit_item(item);
+                }
+            }
+            self.current_node_id = return_id;
+            self.current_level -= 1;
+        }
+
+        fn visit_item_use(&mut self, i: &'ast ItemUse) {
+            let ident = Ident::new("use", Span::call_site());
+            self.add_node(i.into(), ident);
+        }
+
+        fn visit_item_const(&mut self, i: &'ast ItemConst) {
+            self.add_node(i.into(), i.ident.to_owned());
+        }
+
+        fn visit_item_static(&mut self, i: &'ast ItemStatic) {
+            self.add_node(i.into(), i.ident.to_owned());
+        }
+
+        fn visit_item_type(&mut self, i: &'ast ItemType) {
+            self.add_node(i.into(), i.ident.to_owned());
+        }
+
         // top level macro
         fn visit_macro(&mut self, i: &'ast Macro) {
             let ident = match_path(&i.path);
             let ident = ident.first().unwrap();
             self.add_node(i.into(), ident.to_owned());
         }
+
+        fn visit_arm(&mut self, i: &'ast Arm) {
+            let return_id = self.current_node_id;
+            let id = self.add_node(i.into(), match_arm(i));
+            let guard = i.guard.iter().map(|(_, guard)| guard.as_ref());
+            self.visit_nested(id, guard.collect());
+
+            self.current_node_id = id;
+            self.visit_body(&i.body);
+            self.current_node_id = return_id;
+        }
+    }
+
+    impl SyntaxTree {
+        // adds the nodes of the given construct at the current level
+        pub(crate) fn visit_construct(&mut self, construct: &Construct) {
+            match construct {
+                Construct::ItemStruct(item) => self.visit_item_struct(item),
+                Construct::ItemFn(item) => self.visit_item_fn(item),
+                Construct::ItemImpl(item) => self.visit_item_impl(item),
+                Construct::ItemTrait(item) => self.visit_item_trait(item),
+                Construct::ItemEnum(item) => self.visit_item_enum(item),
+                Construct::ItemUnion(item) => self.visit_item_union(item),
+                Construct::ItemConst(item) => self.visit_item_const(item),
+                Construct::ItemStatic(item) => self.visit_item_static(item),
+                Construct::ItemType(item) => self.visit_item_type(item),
+                Construct::ItemMod(item) => self.visit_item_mod(item),
+                Construct::ItemUse(item) => self.visit_item_use(item),
+                Construct::Item(item) => self.visit_item(item),
+                Construct::Macro(mac) => self.visit_macro(mac),
+                Construct::ImplItemFn(item) => {
+                    self.current_level -= 1;
+                    self.visit_impl_item_fn(item);
+                    self.current_level += 1;
+                }
+                Construct::TraitItemFn(item) => {
+                    self.current_level -= 1;
+                    self.visit_trait_item_fn(item);
+                    self.current_level += 1;
+                }
+                Construct::Variant(variant) => {
+                    self.add_node(variant.into(), variant.ident.to_owned());
+                }
+                Construct::Field(field) => {
+                    let ident = field.ident.to_owned();
+                    let ident = ident.unwrap_or_else(|| Ident::new("Field", Span::call_site()));
+                    self.add_node(field.into(), ident);
+                }
+                Construct::Block(block) => {
+                    self.visit_branch(block, "block");
+                }
+                Construct::Arm(arm) => self.visit_arm(arm),
+                Construct::Method(call) => self.visit_method(call),
+                construct => {
+                    if let Some(stmt) = construct.to_owned().into_stmt(false) {
+                        self.visit_stmt(&stmt);
+                    }
+                }
+            }
+        }
+
+        // adds the node of an expression statement and returns its id
+        fn visit_stmt_expr(&mut self, stmt: &Stmt, expr: &Expr) -> usize {
+            let ident = match_expr(expr);
+            let id = match Construct::from_expr(expr.to_owned()) {
+                Some(construct) => self.add_node(construct, ident),
+                None => self.add_node(stmt.into(), ident),
+            };
+            match expr {
+                syn::Expr::Closure(closure) => {
+                    let return_id = self.current_node_id;
+                    self.current_node_id = id;
+                    self.visit_body(&closure.body);
+                    self.current_node_id = return_id;
+                }
+                syn::Expr::Async(block) => {
+                    let return_id = self.current_node_id;
+                    self.current_node_id = id;
+                    self.visit_block(&block.block);
+                    self.current_node_id = return_id;
+                }
+                syn::Expr::MethodCall(call) => self.visit_chain(id, call),
+                expr => {
+                    self.visit_nested(id, nested_heads(expr));
+                    self.visit_branches(id, expr);
+                }
+            }
+            id
+        }
+
+        // adds the nodes of the closures, `async` blocks, chains of method
+        // calls, control flow expressions and blocks nested in the given
+        // expressions as children of the node with the given id, the ones
+        // nested in one of those are children of its node
+        fn visit_nested(&mut self, id: usize, exprs: Vec<&Expr>) {
+            let mut nested = FindNested(vec![]);
+            for expr in exprs {
+                nested.visit_expr(expr);
+            }
+
+            let return_id = self.current_node_id;
+            self.current_node_id = id;
+            self.current_level += 1;
+            for expr in nested.0 {
+                self.visit_stmt_expr(&Stmt::Expr(expr.to_owned(), None), expr);
+            }
+            self.current_level -= 1;
+            self.current_node_id = return_id;
+        }
+
+        // adds the nodes of a chain of method calls as children of the node
+        // with the given id: the receiver of the first call followed by a
+        // node for each call in order
+        fn visit_chain(&mut self, id: usize, call: &ExprMethodCall) {
+            let mut calls = vec![call];
+            let mut receiver = call.receiver.as_ref();
+            while let syn::Expr::MethodCall(call) = receiver {
+                calls.push(call);
+                receiver = &call.receiver;
+            }
+
+            let return_id = self.current_node_id;
+            self.current_node_id = id;
+            self.current_level += 1;
+            self.visit_stmt_expr(&Stmt::Expr(receiver.to_owned(), None), receiver);
+            for call in calls.into_iter().rev() {
+                self.visit_method(call);
+            }
+            self.current_level -= 1;
+            self.current_node_id = return_id;
+        }
+
+        // adds the node of a method call of a chain, the expressions nested
+        // in its arguments are its children
+        fn visit_method(&mut self, call: &ExprMethodCall) {
+            let construct = Construct::Method(call.to_owned());
+            let id = self.add_node(construct, call.method.to_owned());
+            self.visit_nested(id, call.args.iter().collect());
+        }
+
+        // adds the nodes of the statements of the body of a `match` arm or of
+        // a closure, a body without braces has a single statement
+        fn visit_body(&mut self, body: &Expr) {
+            match body {
+                syn::Expr::Block(block) if block.attrs.is_empty() && block.label.is_none() => {
+                    self.visit_block(&block.block)
+                }
+                body => {
+                    self.current_level += 1;
+                    self.visit_stmt(&Stmt::Expr(body.to_owned(), None));
+                    self.current_level -= 1;
+                    self.mark_tail();
+                }
+            }
+        }
+
+        // marks the last child of the current node as the tail expression
+        fn mark_tail(&mut self) {
+            let last = self
+                .get_current_node_mut()
+                .and_then(|node| node.children.last().copied());
+            if let Some(id) = last {
+                if let Some(node) = self.get_node_mut(id) {
+                    node.tail = true;
+                }
+            }
+        }
+
+        // adds the nodes of the branches of a control flow expression as
+        // children of the node with the given id: the `then` and `else`
+        // branches of an `if`, the arms of a `match` and the statements of
+        // the body of a loop or of a block
+        fn visit_branches(&mut self, id: usize, expr: &Expr) {
+            let return_id = self.current_node_id;
+            self.current_node_id = id;
+            match expr {
+                syn::Expr::If(expr_if) => {
+                    self.current_level += 1;
+                    self.visit_branch(&expr_if.then_branch, "then");
+                    match expr_if.else_branch.as_ref().map(|(_, expr)| expr.as_ref()) {
+                        Some(syn::Expr::Block(block)) => self.visit_branch(&block.block, "else"),
+                        // an `else if` is a nested `if` with its own branches
+                        Some(expr @ syn::Expr::If(else_if)) => {
+                            let ident = Ident::new("if", Span::call_site());
+                            let id = self.add_node(Construct::ExprIf(else_if.to_owned()), ident);
+                            self.visit_nested(id, nested_heads(expr));
+                            self.visit_branches(id, expr);
+                        }
+                        _ => (),
+                    }
+                    self.current_level -= 1;
+                }
+                syn::Expr::Match(expr_match) => {
+                    self.current_level += 1;
+                    for arm in &expr_match.arms {
+                        self.visit_arm(arm);
+                    }
+                    self.current_level -= 1;
+                }
+                syn::Expr::Loop(expr_loop) => self.visit_block(&expr_loop.body),
+                syn::Expr::While(expr_while) => self.visit_block(&expr_while.body),
+                syn::Expr::ForLoop(expr_for) => self.visit_block(&expr_for.body),
+                syn::Expr::Block(expr_block) => self.visit_block(&expr_block.block),
+                syn::Expr::Unsafe(expr_unsafe) => self.visit_block(&expr_unsafe.block),
+                syn::Expr::Const(expr_const) => self.visit_block(&expr_const.block),
+                syn::Expr::TryBlock(expr_try) => self.visit_block(&expr_try.block),
+                _ => (),
+            }
+            self.current_node_id = return_id;
+        }
+
+        // adds the node of a branch and the nodes of its statements
+        fn visit_branch(&mut self, block: &Block, name: &str) {
+            let return_id = self.current_node_id;
+            let ident = Ident::new(name, Span::call_site());
+            let id = self.add_node(Construct::Block(block.to_owned()), ident);
+
+            self.current_node_id = id;
+            self.visit_block(block);
+            self.current_node_id = return_id;
+        }
     }
 }
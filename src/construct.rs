@@ -1,15 +1,26 @@
 //! An Enum container for [`syn`] types the input code is parsed into.
 
 use super::utils::*;
-use crate::parse::parse_construct;
+#[cfg(feature = "debug")]
+use crate::import::Import;
+use crate::parse::{is_macro, parse_construct, peek_item};
 use quote::{quote, ToTokens};
-use std::fmt::Debug;
+use std::fmt::{Debug, Display};
+use std::str::FromStr;
 use syn::parse::Parser;
 use syn::{
-    ExprAssign, ExprLet, ExprLit, ExprMacro, ExprPath, ExprReturn, ImplItemFn, ItemFn, ItemImpl,
-    ItemStruct, ItemTrait, Local, Macro, Stmt, StmtMacro, TraitItemFn,
+    Arm, Attribute, Block, Expr, ExprArray, ExprAssign, ExprAsync, ExprAwait, ExprBinary,
+    ExprBlock, ExprBreak, ExprCall, ExprCast, ExprClosure, ExprConst, ExprContinue, ExprField,
+    ExprForLoop, ExprGroup, ExprIf, ExprIndex, ExprInfer, ExprLet, ExprLit, ExprLoop, ExprMacro,
+    ExprMatch, ExprMethodCall, ExprParen, ExprPath, ExprRange, ExprRawAddr, ExprReference,
+    ExprRepeat, ExprReturn, ExprStruct, ExprTry, ExprTryBlock, ExprTuple, ExprUnary, ExprUnsafe,
+    ExprWhile, ExprYield, Field, ImplItem, ImplItemFn, Item, ItemConst, ItemEnum, ItemFn, ItemImpl,
+    ItemMacro, ItemMod, ItemStatic, ItemStruct, ItemTrait, ItemType, ItemUnion, ItemUse, Lifetime,
+    Local, Macro, MacroDelimiter, Stmt, StmtMacro, TraitItem, TraitItemFn, Variant, Visibility,
 };
 
+/// A rust construct stored in a [`Node`](crate::node::Node) as the
+/// corresponding [`syn`] type.
 #[derive(Clone)]
 #[non_exhaustive]
 pub enum Construct {
@@ -17,6 +28,15 @@ pub enum Construct {
     ItemFn(ItemFn),
     ItemImpl(ItemImpl),
     ItemTrait(ItemTrait),
+    ItemEnum(ItemEnum),
+    ItemUnion(ItemUnion),
+    ItemConst(ItemConst),
+    ItemStatic(ItemStatic),
+    ItemType(ItemType),
+    ItemMod(ItemMod),
+    ItemUse(ItemUse),
+    Variant(Variant),
+    Field(Field),
     TraitItemFn(TraitItemFn),
     ImplItemFn(ImplItemFn),
     ExprPath(ExprPath),
@@ -25,10 +45,47 @@ pub enum Construct {
     ExprLit(ExprLit),
     ExprAssign(ExprAssign),
     ExprReturn(ExprReturn),
+    ExprArray(ExprArray),
+    ExprAsync(ExprAsync),
+    ExprAwait(ExprAwait),
+    ExprBinary(ExprBinary),
+    ExprBlock(ExprBlock),
+    ExprBreak(ExprBreak),
+    ExprCall(ExprCall),
+    ExprCast(ExprCast),
+    ExprClosure(ExprClosure),
+    ExprConst(ExprConst),
+    ExprContinue(ExprContinue),
+    ExprField(ExprField),
+    ExprForLoop(ExprForLoop),
+    ExprGroup(ExprGroup),
+    ExprIf(ExprIf),
+    ExprIndex(ExprIndex),
+    ExprInfer(ExprInfer),
+    ExprLoop(ExprLoop),
+    ExprMatch(ExprMatch),
+    ExprMethodCall(ExprMethodCall),
+    ExprParen(ExprParen),
+    ExprRange(ExprRange),
+    ExprRawAddr(ExprRawAddr),
+    ExprReference(ExprReference),
+    ExprRepeat(ExprRepeat),
+    ExprStruct(ExprStruct),
+    ExprTry(ExprTry),
+    ExprTryBlock(ExprTryBlock),
+    ExprTuple(ExprTuple),
+    ExprUnary(ExprUnary),
+    ExprUnsafe(ExprUnsafe),
+    ExprWhile(ExprWhile),
+    ExprYield(ExprYield),
+    Block(Block),
+    Arm(Arm),
+    Method(ExprMethodCall),
     Local(Local),
     Stmt(Stmt),
     StmtMacro(StmtMacro),
     Macro(Macro),
+    Item(Item),
     Root,
     None,
 }
@@ -68,6 +125,11 @@ macro_rules! impl_construct {
                     $(
                         Construct::$typ(item) => quote! {#item},
                     )*
+                    Construct::Field(field) => quote! {#field},
+                    Construct::Local(local) => quote! {#local},
+                    Construct::StmtMacro(mac) => quote! {#mac},
+                    Construct::ExprGroup(expr) => quote! {#expr},
+                    Construct::Method(call) => quote! {#call},
                     _ => quote! {},
                 }
             }
@@ -79,6 +141,11 @@ macro_rules! impl_construct {
                     $(
                         Construct::$typ(item) => item.to_tokens(tokens),
                     )*
+                    Construct::Field(field) => field.to_tokens(tokens),
+                    Construct::Local(local) => local.to_tokens(tokens),
+                    Construct::StmtMacro(mac) => mac.to_tokens(tokens),
+                    Construct::ExprGroup(expr) => expr.to_tokens(tokens),
+                    Construct::Method(call) => call.to_tokens(tokens),
                     _ => (),
                 }
             }
@@ -91,6 +158,14 @@ impl_construct!(
     ItemFn
     ItemImpl
     ItemTrait
+    ItemEnum
+    ItemUnion
+    ItemConst
+    ItemStatic
+    ItemType
+    ItemMod
+    ItemUse
+    Variant
     TraitItemFn
     ImplItemFn
     ExprPath
@@ -98,9 +173,44 @@ impl_construct!(
     ExprLit
     ExprAssign
     ExprReturn
+    ExprArray
+    ExprAsync
+    ExprAwait
+    ExprBinary
+    ExprBlock
+    ExprBreak
+    ExprCall
+    ExprCast
+    ExprClosure
+    ExprConst
+    ExprContinue
+    ExprField
+    ExprForLoop
+    ExprIf
+    ExprIndex
+    ExprInfer
+    ExprLoop
+    ExprMatch
+    ExprMethodCall
+    ExprParen
+    ExprRange
+    ExprRawAddr
+    ExprReference
+    ExprRepeat
+    ExprStruct
+    ExprTry
+    ExprTryBlock
+    ExprTuple
+    ExprUnary
+    ExprUnsafe
+    ExprWhile
+    ExprYield
+    Block
+    Arm
     Stmt
     Macro
     ExprMacro
+    Item
 );
 
 // StmtMacro does not implement the Parse trait
@@ -110,25 +220,561 @@ impl From<&syn::StmtMacro> for Construct {
     }
 }
 
+// Field does not implement the Parse trait
+impl From<&syn::Field> for Construct {
+    fn from(field: &syn::Field) -> Self {
+        Construct::Field(field.to_owned())
+    }
+}
+
+// ExprGroup does not implement the Parse trait
+impl From<&syn::ExprGroup> for Construct {
+    fn from(expr: &syn::ExprGroup) -> Self {
+        Construct::ExprGroup(expr.to_owned())
+    }
+}
+
+macro_rules! construct_expr {
+    ($($typ:ident $variant:ident)+) => {
+        impl Construct {
+            /// Makes the construct of an expression, or `None` for the
+            /// expressions that have no construct (e.g. `Expr::Verbatim`).
+            pub(crate) fn from_expr(expr: Expr) -> Option<Construct> {
+                match expr {
+                    $(Expr::$variant(expr) => Some(Construct::$typ(expr)),)+
+                    _ => None,
+                }
+            }
+
+            /// Converts the construct into an expression.
+            pub(crate) fn into_expr(self) -> Option<Expr> {
+                match self {
+                    $(Construct::$typ(expr) => Some(Expr::$variant(expr)),)+
+                    _ => None,
+                }
+            }
+
+            /// Returns `true` if the construct is an expression.
+            pub(crate) fn is_expr(&self) -> bool {
+                matches!(self, $(Construct::$typ(_))|+)
+            }
+
+            // the attributes of an expression
+            fn expr_attrs(&self) -> &[Attribute] {
+                match self {
+                    $(Construct::$typ(expr) => &expr.attrs,)+
+                    _ => &[],
+                }
+            }
+        }
+    };
+}
+
+construct_expr!(
+    ExprArray       Array
+    ExprAssign      Assign
+    ExprAsync       Async
+    ExprAwait       Await
+    ExprBinary      Binary
+    ExprBlock       Block
+    ExprBreak       Break
+    ExprCall        Call
+    ExprCast        Cast
+    ExprClosure     Closure
+    ExprConst       Const
+    ExprContinue    Continue
+    ExprField       Field
+    ExprForLoop     ForLoop
+    ExprGroup       Group
+    ExprIf          If
+    ExprIndex       Index
+    ExprInfer       Infer
+    ExprLet         Let
+    ExprLit         Lit
+    ExprLoop        Loop
+    ExprMacro       Macro
+    ExprMatch       Match
+    ExprMethodCall  MethodCall
+    ExprParen       Paren
+    ExprPath        Path
+    ExprRange       Range
+    ExprRawAddr     RawAddr
+    ExprReference   Reference
+    ExprRepeat      Repeat
+    ExprReturn      Return
+    ExprStruct      Struct
+    ExprTry         Try
+    ExprTryBlock    TryBlock
+    ExprTuple       Tuple
+    ExprUnary       Unary
+    ExprUnsafe      Unsafe
+    ExprWhile       While
+    ExprYield       Yield
+);
+
+macro_rules! construct_kind {
+    ($($kind:ident $name:ident)+) => {
+        /// The kind of a [`Construct`], i.e. its variant without the [`syn`] type.
+        ///
+        /// Every kind has a stable name in snake case (e.g. `item_fn`), used
+        /// by its `Display` and `FromStr` implementations.
+        #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+        #[non_exhaustive]
+        pub enum ConstructKind {
+            $($kind,)+
+        }
+
+        impl ConstructKind {
+            /// All the kinds of constructs.
+            pub const ALL: &'static [ConstructKind] = &[$(ConstructKind::$kind,)+];
+
+            /// Returns the stable name of the kind, e.g. `item_fn`.
+            pub fn name(&self) -> &'static str {
+                match self {
+                    $(ConstructKind::$kind => stringify!($name),)+
+                }
+            }
+        }
+
+        impl Construct {
+            /// Returns the kind of the construct.
+            pub fn kind(&self) -> ConstructKind {
+                match self {
+                    $(Construct::$kind { .. } => ConstructKind::$kind,)+
+                }
+            }
+        }
+    };
+}
+
+construct_kind!(
+    ItemStruct      item_struct
+    ItemFn          item_fn
+    ItemImpl        item_impl
+    ItemTrait       item_trait
+    ItemEnum        item_enum
+    ItemUnion       item_union
+    ItemConst       item_const
+    ItemStatic      item_static
+    ItemType        item_type
+    ItemMod         item_mod
+    ItemUse         item_use
+    Variant         variant
+    Field           field
+    TraitItemFn     trait_item_fn
+    ImplItemFn      impl_item_fn
+    ExprPath        expr_path
+    ExprMacro       expr_macro
+    ExprLet         expr_let
+    ExprLit         expr_lit
+    ExprAssign      expr_assign
+    ExprReturn      expr_return
+    ExprArray       expr_array
+    ExprAsync       expr_async
+    ExprAwait       expr_await
+    ExprBinary      expr_binary
+    ExprBlock       expr_block
+    ExprBreak       expr_break
+    ExprCall        expr_call
+    ExprCast        expr_cast
+    ExprClosure     expr_closure
+    ExprConst       expr_const
+    ExprContinue    expr_continue
+    ExprField       expr_field
+    ExprForLoop     expr_for_loop
+    ExprGroup       expr_group
+    ExprIf          expr_if
+    ExprIndex       expr_index
+    ExprInfer       expr_infer
+    ExprLoop        expr_loop
+    ExprMatch       expr_match
+    ExprMethodCall  expr_method_call
+    ExprParen       expr_paren
+    ExprRange       expr_range
+    ExprRawAddr     expr_raw_addr
+    ExprReference   expr_reference
+    ExprRepeat      expr_repeat
+    ExprStruct      expr_struct
+    ExprTry         expr_try
+    ExprTryBlock    expr_try_block
+    ExprTuple       expr_tuple
+    ExprUnary       expr_unary
+    ExprUnsafe      expr_unsafe
+    ExprWhile       expr_while
+    ExprYield       expr_yield
+    Block           block
+    Arm             arm
+    Method          method
+    Local           local
+    Stmt            stmt
+    StmtMacro       stmt_macro
+    Macro           macro
+    Item            item
+    Root            root
+    None            none
+);
+
+impl Display for ConstructKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for ConstructKind {
+    type Err = ParseKindError;
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        let kind = ConstructKind::ALL.iter().find(|kind| kind.name() == name);
+        kind.copied().ok_or_else(|| ParseKindError {
+            name: name.to_string(),
+        })
+    }
+}
+
+/// The error returned when a string is not the name of a [`ConstructKind`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ParseKindError {
+    name: String,
+}
+
+impl Display for ParseKindError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "unknown construct kind `{}`", self.name)
+    }
+}
+
+impl std::error::Error for ParseKindError {}
+
+impl Construct {
+    /// Returns the outer attributes of the construct.
+    pub(crate) fn attrs(&self) -> &[Attribute] {
+        match self {
+            Construct::ItemStruct(item) => &item.attrs,
+            Construct::ItemFn(item) => &item.attrs,
+            Construct::ItemImpl(item) => &item.attrs,
+            Construct::ItemTrait(item) => &item.attrs,
+            Construct::ItemEnum(item) => &item.attrs,
+            Construct::ItemUnion(item) => &item.attrs,
+            Construct::ItemConst(item) => &item.attrs,
+            Construct::ItemStatic(item) => &item.attrs,
+            Construct::ItemType(item) => &item.attrs,
+            Construct::ItemMod(item) => &item.attrs,
+            Construct::ItemUse(item) => &item.attrs,
+            Construct::Variant(variant) => &variant.attrs,
+            Construct::Field(field) => &field.attrs,
+            Construct::TraitItemFn(item) => &item.attrs,
+            Construct::ImplItemFn(item) => &item.attrs,
+            Construct::Arm(arm) => &arm.attrs,
+            Construct::Method(call) => &call.attrs,
+            Construct::Local(local) => &local.attrs,
+            Construct::Stmt(stmt) => match stmt {
+                Stmt::Local(local) => &local.attrs,
+                Stmt::Item(item) => match_item_attrs(item),
+                Stmt::Expr(expr, _) => match_expr_attrs(expr),
+                Stmt::Macro(mac) => &mac.attrs,
+            },
+            Construct::StmtMacro(mac) => &mac.attrs,
+            Construct::Item(item) => match_item_attrs(item),
+            construct => construct.expr_attrs(),
+        }
+    }
+
+    /// Returns the visibility of the construct or `None` if the construct
+    /// cannot have a visibility.
+    pub(crate) fn vis(&self) -> Option<&Visibility> {
+        match self {
+            Construct::ItemStruct(item) => Some(&item.vis),
+            Construct::ItemFn(item) => Some(&item.vis),
+            Construct::ItemTrait(item) => Some(&item.vis),
+            Construct::ItemEnum(item) => Some(&item.vis),
+            Construct::ItemUnion(item) => Some(&item.vis),
+            Construct::ItemConst(item) => Some(&item.vis),
+            Construct::ItemStatic(item) => Some(&item.vis),
+            Construct::ItemType(item) => Some(&item.vis),
+            Construct::ItemMod(item) => Some(&item.vis),
+            Construct::ItemUse(item) => Some(&item.vis),
+            Construct::Field(field) => Some(&field.vis),
+            Construct::ImplItemFn(item) => Some(&item.vis),
+            Construct::Stmt(Stmt::Item(item)) | Construct::Item(item) => match_item_vis(item),
+            _ => None,
+        }
+    }
+
+    /// Returns the label of the construct or `None` if the construct is not
+    /// a labeled loop or block.
+    pub(crate) fn label(&self) -> Option<&Lifetime> {
+        let label = match self {
+            Construct::ExprLoop(expr) => &expr.label,
+            Construct::ExprWhile(expr) => &expr.label,
+            Construct::ExprForLoop(expr) => &expr.label,
+            Construct::ExprBlock(expr) => &expr.label,
+            _ => return None,
+        };
+        label.as_ref().map(|label| &label.name)
+    }
+
+    /// Returns `true` if the children of the construct are the statements of
+    /// its body, e.g. a function or a loop.
+    pub(crate) fn has_stmts(&self) -> bool {
+        match self {
+            Construct::Stmt(Stmt::Local(local)) => matches!(
+                local.init.as_ref().map(|init| init.expr.as_ref()),
+                Some(
+                    Expr::Block(_)
+                        | Expr::Loop(_)
+                        | Expr::While(_)
+                        | Expr::ForLoop(_)
+                        | Expr::Unsafe(_)
+                        | Expr::Const(_)
+                        | Expr::TryBlock(_)
+                )
+            ),
+            construct => matches!(
+                construct,
+                Construct::ItemFn(_)
+                    | Construct::ImplItemFn(_)
+                    | Construct::TraitItemFn(_)
+                    | Construct::Block(_)
+                    | Construct::Arm(_)
+                    | Construct::ExprLoop(_)
+                    | Construct::ExprWhile(_)
+                    | Construct::ExprForLoop(_)
+                    | Construct::ExprClosure(_)
+                    | Construct::ExprAsync(_)
+                    | Construct::ExprBlock(_)
+                    | Construct::ExprUnsafe(_)
+                    | Construct::ExprConst(_)
+                    | Construct::ExprTryBlock(_)
+            ),
+        }
+    }
+
+    /// Returns `true` if the construct is an expression that needs a semicolon
+    /// when another statement follows it, i.e. it does not end with a block.
+    pub(crate) fn needs_semi(&self) -> bool {
+        self.is_expr()
+            && !matches!(
+                self,
+                Construct::ExprIf(_)
+                    | Construct::ExprMatch(_)
+                    | Construct::ExprLoop(_)
+                    | Construct::ExprWhile(_)
+                    | Construct::ExprForLoop(_)
+                    | Construct::ExprBlock(_)
+                    | Construct::ExprUnsafe(_)
+                    | Construct::ExprConst(_)
+                    | Construct::ExprTryBlock(_)
+            )
+    }
+
+    /// Returns `true` if the given construct can be a child of the construct,
+    /// i.e. it is part of the construct when it is rebuilt from its children.
+    pub(crate) fn can_contain(&self, child: &Construct) -> bool {
+        match self {
+            Construct::ItemImpl(_) => matches!(child, Construct::ImplItemFn(_)),
+            Construct::ItemTrait(_) => matches!(child, Construct::TraitItemFn(_)),
+            Construct::ItemEnum(_) => matches!(child, Construct::Variant(_)),
+            Construct::ItemUnion(_) => matches!(child, Construct::Field(_)),
+            Construct::ExprMatch(_) => matches!(child, Construct::Arm(_)),
+            Construct::ExprMethodCall(_) => matches!(child, Construct::Method(_)),
+            Construct::ItemMod(_) | Construct::Root => child.to_owned().into_item().is_some(),
+            parent if parent.has_stmts() => child.to_owned().into_stmt(false).is_some(),
+            _ => false,
+        }
+    }
+
+    /// Converts the construct into a variant of an `enum`.
+    pub(crate) fn into_variant(self) -> Option<Variant> {
+        match self {
+            Construct::Variant(variant) => Some(variant),
+            _ => None,
+        }
+    }
+
+    /// Converts the construct into an arm of a `match` expression.
+    pub(crate) fn into_arm(self) -> Option<Arm> {
+        match self {
+            Construct::Arm(arm) => Some(arm),
+            _ => None,
+        }
+    }
+
+    /// Converts the construct into a field of a `struct` or `union`.
+    pub(crate) fn into_field(self) -> Option<Field> {
+        match self {
+            Construct::Field(field) => Some(field),
+            _ => None,
+        }
+    }
+
+    /// Converts the construct into a statement of a block. Expressions do not
+    /// store their trailing semicolon, which is then taken from `semi`.
+    pub(crate) fn into_stmt(self, semi: bool) -> Option<Stmt> {
+        let semi = semi.then(Default::default);
+        let stmt = match self {
+            Construct::Stmt(stmt) => stmt,
+            Construct::Local(local) => Stmt::Local(local),
+            Construct::StmtMacro(mac) => Stmt::Macro(mac),
+            Construct::Macro(mac) => Stmt::Macro(StmtMacro {
+                attrs: vec![],
+                semi_token: macro_semi(&mac),
+                mac,
+            }),
+            Construct::ItemStruct(item) => Stmt::Item(Item::Struct(item)),
+            Construct::ItemFn(item) => Stmt::Item(Item::Fn(item)),
+            Construct::ItemImpl(item) => Stmt::Item(Item::Impl(item)),
+            Construct::ItemTrait(item) => Stmt::Item(Item::Trait(item)),
+            Construct::ItemEnum(item) => Stmt::Item(Item::Enum(item)),
+            Construct::ItemUnion(item) => Stmt::Item(Item::Union(item)),
+            Construct::ItemConst(item) => Stmt::Item(Item::Const(item)),
+            Construct::ItemStatic(item) => Stmt::Item(Item::Static(item)),
+            Construct::ItemType(item) => Stmt::Item(Item::Type(item)),
+            Construct::ItemMod(item) => Stmt::Item(Item::Mod(item)),
+            Construct::ItemUse(item) => Stmt::Item(Item::Use(item)),
+            Construct::Item(item) => Stmt::Item(item),
+            construct => Stmt::Expr(construct.into_expr()?, semi),
+        };
+        Some(stmt)
+    }
+
+    /// Converts the construct into an item of a module.
+    pub(crate) fn into_item(self) -> Option<Item> {
+        match self.into_stmt(false)? {
+            Stmt::Item(item) => Some(item),
+            Stmt::Macro(mac) => Some(Item::Macro(ItemMacro {
+                attrs: mac.attrs,
+                ident: None,
+                mac: mac.mac,
+                semi_token: mac.semi_token,
+            })),
+            _ => None,
+        }
+    }
+
+    /// Converts the construct into an item of an `impl` block.
+    pub(crate) fn into_impl_item(self) -> Option<ImplItem> {
+        match self {
+            Construct::ImplItemFn(item) => Some(ImplItem::Fn(item)),
+            _ => None,
+        }
+    }
+
+    /// Converts the construct into an item of a `trait` definition.
+    pub(crate) fn into_trait_item(self) -> Option<TraitItem> {
+        match self {
+            Construct::TraitItemFn(item) => Some(TraitItem::Fn(item)),
+            _ => None,
+        }
+    }
+}
+
+// a macro in statement or item position needs a semicolon unless it is
+// delimited by braces
+pub(crate) fn macro_semi(mac: &Macro) -> Option<syn::Token![;]> {
+    match mac.delimiter {
+        MacroDelimiter::Brace(_) => None,
+        _ => Some(Default::default()),
+    }
+}
+
 pub(crate) fn make_construct_form_tokens(
     tokens: proc_macro2::TokenStream,
 ) -> syn::Result<Construct> {
     parse_single.parse2(tokens)
 }
 
+// parses the tokens into a construct that can take the place of the given
+// construct, e.g. a method of an `impl` block or a statement of a function
+pub(crate) fn make_construct_like(
+    tokens: proc_macro2::TokenStream,
+    like: &Construct,
+) -> syn::Result<Construct> {
+    match like {
+        Construct::ImplItemFn(_) => Ok(Construct::ImplItemFn(syn::parse2(tokens)?)),
+        Construct::TraitItemFn(_) => Ok(Construct::TraitItemFn(syn::parse2(tokens)?)),
+        Construct::Variant(_) => Ok(Construct::Variant(syn::parse2(tokens)?)),
+        Construct::Field(_) => Ok(Construct::Field(Field::parse_named.parse2(tokens)?)),
+        Construct::Block(_) => Ok(Construct::Block(syn::parse2(tokens)?)),
+        Construct::Arm(_) => Ok(Construct::Arm(syn::parse2(tokens)?)),
+        Construct::Method(_) => make_method_from_tokens(tokens),
+        Construct::Stmt(_) | Construct::Local(_) | Construct::StmtMacro(_) => {
+            make_stmt_from_tokens(tokens)
+        }
+        like if like.is_expr() => make_stmt_from_tokens(tokens),
+        _ => make_item_from_tokens(tokens),
+    }
+}
+
+// parses the tokens into a construct that can be a child of the given
+// construct, e.g. a method of an `impl` block or a statement of a function
+pub(crate) fn make_child_construct(
+    tokens: proc_macro2::TokenStream,
+    parent: &Construct,
+) -> syn::Result<Construct> {
+    match parent {
+        Construct::ItemImpl(_) => Ok(Construct::ImplItemFn(syn::parse2(tokens)?)),
+        Construct::ItemTrait(_) => Ok(Construct::TraitItemFn(syn::parse2(tokens)?)),
+        Construct::ItemEnum(_) => Ok(Construct::Variant(syn::parse2(tokens)?)),
+        Construct::ItemUnion(_) => Ok(Construct::Field(Field::parse_named.parse2(tokens)?)),
+        Construct::ExprMatch(_) => Ok(Construct::Arm(syn::parse2(tokens)?)),
+        Construct::ExprMethodCall(_) => make_method_from_tokens(tokens),
+        parent if parent.has_stmts() => make_stmt_from_tokens(tokens),
+        Construct::ItemMod(_) | Construct::Root => make_item_from_tokens(tokens),
+        _ => Err(syn::Error::new_spanned(
+            tokens,
+            "the construct cannot have children",
+        )),
+    }
+}
+
+// parses the tokens into a single statement
+fn make_stmt_from_tokens(tokens: proc_macro2::TokenStream) -> syn::Result<Construct> {
+    let mut stmts = Block::parse_within.parse2(tokens.clone())?;
+    match (stmts.pop(), stmts.is_empty()) {
+        (Some(stmt), true) => Ok(Construct::Stmt(stmt)),
+        _ => Err(syn::Error::new_spanned(
+            tokens,
+            "expected a single statement",
+        )),
+    }
+}
+
+// parses the tokens into a method call of a chain without its receiver,
+// e.g. `.unwrap()`
+fn make_method_from_tokens(tokens: proc_macro2::TokenStream) -> syn::Result<Construct> {
+    match syn::parse2(quote! { receiver #tokens }) {
+        Ok(Expr::MethodCall(call)) if matches!(call.receiver.as_ref(), Expr::Path(_)) => {
+            Ok(Construct::Method(call))
+        }
+        _ => Err(syn::Error::new_spanned(
+            tokens,
+            "expected a method call, e.g. `.unwrap()`",
+        )),
+    }
+}
+
+// parses the tokens into a single item or macro
+fn make_item_from_tokens(tokens: proc_macro2::TokenStream) -> syn::Result<Construct> {
+    match make_construct_form_tokens(tokens.clone())? {
+        Construct::None => Err(syn::Error::new_spanned(tokens, "expected a construct")),
+        construct => Ok(construct),
+    }
+}
+
 macro_rules! parse_into_construct {
     () => {};
     ($($name:ident $typ:ident)+) => {
-        fn parse_single<'ast>(stream: syn::parse::ParseStream) -> syn::Result<Construct> {
+        fn parse_single(stream: syn::parse::ParseStream) -> syn::Result<Construct> {
             while !stream.cursor().eof() {
                 $(
-                    if stream.peek(syn::Token![$name]) || stream.peek2(syn::Token![$name]){
+                    if peek_item(stream, syn::Token![$name]) {
                         return Ok(Construct::$typ(parse_construct(stream)?))
                     };
                 )*
-                if stream.peek(syn::Ident) && stream.peek2(syn::Token![!]) {
+                if is_macro(stream) {
                     return Ok(Construct::Macro(parse_construct(stream)?))
                 }
+                return Ok(Construct::Item(parse_construct(stream)?))
             }
 
             Ok(Construct::None)
@@ -143,14 +789,21 @@ parse_into_construct!(
     fn      ItemFn
     impl    ItemImpl
     trait   ItemTrait
+    enum    ItemEnum
+    union   ItemUnion
+    const   ItemConst
+    static  ItemStatic
+    type    ItemType
+    mod     ItemMod
+    use     ItemUse
 );
 
 #[cfg(feature = "debug")]
 impl Debug for Construct {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         let name = match self {
-            Construct::ItemStruct(strct) => format!("ItemStruct: {}", strct.ident).to_string(),
-            Construct::ItemFn(func) => format!("ItemFn: {}", func.sig.ident).to_string(),
+            Construct::ItemStruct(strct) => format!("ItemStruct: {}", strct.ident),
+            Construct::ItemFn(func) => format!("ItemFn: {}", func.sig.ident),
             Construct::ItemImpl(item_impl) => {
                 if let Some(t) = item_impl.trait_.to_owned() {
                     let pat = match_path(&t.1);
@@ -160,8 +813,24 @@ impl Debug for Construct {
                 }
             }
             Construct::ItemTrait(item_trait) => {
-                format!("Trait: {}", item_trait.ident.to_string())
+                format!("Trait: {}", item_trait.ident)
+            }
+            Construct::ItemEnum(item_enum) => format!("Enum: {}", item_enum.ident),
+            Construct::ItemUnion(item_union) => format!("Union: {}", item_union.ident),
+            Construct::ItemConst(item_const) => format!("Const: {}", item_const.ident),
+            Construct::ItemStatic(item_static) => format!("Static: {}", item_static.ident),
+            Construct::ItemType(item_type) => format!("Type: {}", item_type.ident),
+            Construct::ItemMod(item_mod) => format!("Mod: {}", item_mod.ident),
+            Construct::ItemUse(item_use) => {
+                let imports = Import::from_item_use(item_use);
+                let imports = imports.iter().map(Import::to_string);
+                format!("Use: {}", imports.collect::<Vec<String>>().join(", "))
             }
+            Construct::Variant(variant) => format!("Variant: {}", variant.ident),
+            Construct::Field(field) => match &field.ident {
+                Some(ident) => format!("Field: {ident}"),
+                None => "Field".to_string(),
+            },
             Construct::TraitItemFn(trait_item_fn) => {
                 format!("TraitItemFn: {}", trait_item_fn.sig.ident)
             }
@@ -169,10 +838,9 @@ impl Debug for Construct {
                 format!("ImplItemFn: {}", impl_item_fn.sig.ident)
             }
             Construct::ExprPath(pat) => format!("ExprPath: {:?}", match_path(&pat.path)),
-            Construct::Local(local) => format!(
-                "Let {}",
-                format!("{}", match_pat(&local.pat).first().unwrap())
-            ),
+            Construct::Local(local) => {
+                format!("Let {}", match_pat(&local.pat).first().unwrap())
+            }
             Construct::Stmt(stmt) => match stmt {
                 syn::Stmt::Local(local) => {
                     format!("Stmt: Local: {}", match_pat(&local.pat).first().unwrap())
@@ -183,21 +851,26 @@ impl Debug for Construct {
             },
             Construct::StmtMacro(mac) => format!("Stmt: Macro: {:?}", match_path(&mac.mac.path)),
             Construct::Macro(mac) => format!("Macro: {:?}", match_path(&mac.path)),
+            Construct::Item(item) => format!("Item: {}", match_item_ident(item)),
             Construct::ExprMacro(expr_mac) => {
                 format!("ExprMacro: {:?}", match_path(&expr_mac.mac.path))
             }
             Construct::ExprReturn(ret) => {
                 if let Some(expr) = &ret.expr {
-                    format!("{}", match_expr(&expr))
+                    match_expr(expr).to_string()
                 } else {
                     "ExprReturn".to_string()
                 }
             }
-            Construct::ExprAssign(assign) => format!("{}", match_expr(&assign.left)),
-            Construct::ExprLet(let_expr) => format!("{}", match_expr(&let_expr.expr)),
-            Construct::ExprLit(lit_expr) => format!("{}", match_lit_expr(&lit_expr.lit)),
+            Construct::ExprAssign(assign) => match_expr(&assign.left).to_string(),
+            Construct::ExprLet(let_expr) => match_expr(&let_expr.expr).to_string(),
+            Construct::ExprLit(lit_expr) => match_lit_expr(&lit_expr.lit).to_string(),
             Construct::Root => "Root".to_string(),
             Construct::None => "None".to_string(),
+            construct => match construct.to_owned().into_expr() {
+                Some(expr) => format!("{:?}: {}", construct.kind(), match_expr(&expr)),
+                None => format!("{:?}", construct.kind()),
+            },
         };
         write!(f, "{}", name)
     }
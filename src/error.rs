@@ -0,0 +1,170 @@
+//! The error type of the parsing functions that read source text
+//! (e.g. [`parse_str`](crate::parse_str) or [`parse_file`](crate::parse_file))
+//! and of the functions that edit a [`SyntaxTree`](crate::ast::SyntaxTree).
+//!
+//! Positions are derived from the span locations of [`proc_macro2`], so they
+//! refer to the parsed source text when the parsing is performed outside of
+//! a procedural macro.
+
+use crate::span::Position;
+use std::fmt::Display;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// The message of the error returned when the parser does not make progress.
+pub(crate) const NO_PROGRESS: &str = "the parser did not make progress at this token";
+
+/// A `Result` with the error type of this crate.
+pub type Result<T> = std::result::Result<T, Error>;
+
+/// The errors that can occur when parsing source text.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum Error {
+    /// The source text is not a valid sequence of tokens, e.g. it contains
+    /// an unterminated string literal or unbalanced delimiters.
+    Lex {
+        file: Option<PathBuf>,
+        start: Position,
+    },
+
+    /// The tokens do not form a construct the parser recognizes.
+    Unsupported {
+        file: Option<PathBuf>,
+        message: String,
+        start: Position,
+        end: Position,
+    },
+
+    /// The parser stopped because it did not make progress on the input.
+    NoProgress {
+        file: Option<PathBuf>,
+        start: Position,
+    },
+
+    /// A source file could not be read.
+    Io { file: PathBuf, source: io::Error },
+
+    /// The tree has no node with the given id.
+    NodeNotFound { id: usize },
+
+    /// The node with the given id has no child position with the given index.
+    InvalidIndex { id: usize, index: usize },
+
+    /// The node with the given id cannot be a child of the node `parent`,
+    /// e.g. a method of an `impl` block at the top level of a file, or cannot
+    /// be removed from it, e.g. the receiver of a chain of method calls.
+    InvalidChild { id: usize, parent: usize },
+
+    /// A selector string is malformed, `offset` is the byte offset in the
+    /// selector where the error was found.
+    InvalidSelector {
+        selector: String,
+        message: String,
+        offset: usize,
+    },
+}
+
+impl Error {
+    /// Returns the path of the file the error occurred in, if any.
+    pub fn file(&self) -> Option<&Path> {
+        match self {
+            Error::Lex { file, .. }
+            | Error::Unsupported { file, .. }
+            | Error::NoProgress { file, .. } => file.as_deref(),
+            Error::Io { file, .. } => Some(file),
+            _ => None,
+        }
+    }
+
+    /// Returns the position in the source text where the error starts or
+    /// `None` for an error that does not refer to the source text.
+    pub fn start(&self) -> Option<Position> {
+        match self {
+            Error::Lex { start, .. }
+            | Error::Unsupported { start, .. }
+            | Error::NoProgress { start, .. } => Some(*start),
+            _ => None,
+        }
+    }
+
+    /// Sets the path of the file the error occurred in.
+    pub(crate) fn in_file(mut self, path: &Path) -> Self {
+        match &mut self {
+            Error::Lex { file, .. }
+            | Error::Unsupported { file, .. }
+            | Error::NoProgress { file, .. } => *file = Some(path.to_path_buf()),
+            _ => (),
+        }
+        self
+    }
+}
+
+impl From<syn::Error> for Error {
+    fn from(err: syn::Error) -> Self {
+        let span = err.span();
+        let message = err.to_string();
+
+        if message == NO_PROGRESS {
+            Error::NoProgress {
+                file: None,
+                start: Position::start_of(span),
+            }
+        } else {
+            Error::Unsupported {
+                file: None,
+                message,
+                start: Position::start_of(span),
+                end: Position::end_of(span),
+            }
+        }
+    }
+}
+
+impl From<proc_macro2::LexError> for Error {
+    fn from(err: proc_macro2::LexError) -> Self {
+        Error::Lex {
+            file: None,
+            start: Position::start_of(err.span()),
+        }
+    }
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if let Some(file) = self.file() {
+            write!(f, "{}:", file.display())?;
+        }
+
+        match self {
+            Error::Lex { start, .. } => write!(f, "{start}: cannot tokenize the source"),
+            Error::Unsupported { start, message, .. } => write!(f, "{start}: {message}"),
+            Error::NoProgress { start, .. } => write!(f, "{start}: {NO_PROGRESS}"),
+            Error::Io { source, .. } => write!(f, " {source}"),
+            Error::NodeNotFound { id } => write!(f, "no node with id {id}"),
+            Error::InvalidIndex { id, index } => {
+                write!(f, "node {id} has no child position {index}")
+            }
+            Error::InvalidChild { id, parent } => {
+                write!(
+                    f,
+                    "node {id} cannot be moved to or removed from node {parent}"
+                )
+            }
+            Error::InvalidSelector {
+                selector,
+                message,
+                offset,
+            } => write!(f, "invalid selector `{selector}` at {offset}: {message}"),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Io { source, .. } => Some(source),
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,165 @@
+//! Parsing of source files and of whole crates.
+//!
+//! A crate is parsed starting from its root file (e.g. `src/lib.rs`). Every
+//! `mod foo;` declaration is replaced by the items of the file of the module,
+//! so that the crate is represented by a single tree with module nodes.
+
+use crate::ast::SyntaxTree;
+use crate::error::{Error, Result};
+use crate::parse;
+use proc_macro2::TokenStream;
+use std::path::{Path, PathBuf};
+use std::{fs, io};
+use syn::{Item, ItemMod, Lit, Meta};
+
+/// The directories the modules declared in a file or in an inline module
+/// are looked up in.
+struct ModuleDir {
+    /// Directory of the files of the child modules, e.g. `src/a` for `src/a.rs`.
+    dir: PathBuf,
+
+    /// Directory `#[path]` attributes are relative to.
+    path_base: PathBuf,
+}
+
+impl ModuleDir {
+    // the directories for the modules declared at the top of the given file.
+    // Only files named `mod.rs` and the crate root (or files loaded with a
+    // `#[path]` attribute) own the directory they are in.
+    fn of_file(file: &Path, owns_dir: bool) -> ModuleDir {
+        let parent = file.parent().unwrap_or(Path::new("")).to_path_buf();
+        let dir = match file.file_stem() {
+            Some(stem) if !owns_dir => parent.join(stem),
+            _ => parent.clone(),
+        };
+
+        ModuleDir {
+            dir,
+            path_base: parent,
+        }
+    }
+
+    // the directories for the modules declared in an inline module
+    fn of_inline(&self, name: &str) -> ModuleDir {
+        let dir = self.dir.join(name);
+        ModuleDir {
+            dir: dir.to_owned(),
+            path_base: dir,
+        }
+    }
+}
+
+pub(crate) fn parse_file(path: &Path) -> Result<SyntaxTree> {
+    let file = read_file(path)?;
+    Ok(parse::parse_file(&file))
+}
+
+pub(crate) fn parse_crate(root: &Path) -> Result<SyntaxTree> {
+    let root = crate_root(root)?;
+    let mut file = read_file(&root)?;
+    load_modules(&mut file.items, &ModuleDir::of_file(&root, true))?;
+    Ok(parse::parse_file(&file))
+}
+
+// the root file of a crate given either the file itself or the directory of
+// the crate
+fn crate_root(root: &Path) -> Result<PathBuf> {
+    if !root.is_dir() {
+        return Ok(root.to_path_buf());
+    }
+
+    let candidates = ["src/lib.rs", "src/main.rs", "lib.rs", "main.rs"];
+    candidates
+        .iter()
+        .map(|candidate| root.join(candidate))
+        .find(|candidate| candidate.is_file())
+        .ok_or_else(|| Error::Io {
+            file: root.to_path_buf(),
+            source: io::Error::new(io::ErrorKind::NotFound, "no crate root file found"),
+        })
+}
+
+fn read_file(path: &Path) -> Result<syn::File> {
+    let source = fs::read_to_string(path).map_err(|source| Error::Io {
+        file: path.to_path_buf(),
+        source,
+    })?;
+    parse_source(&source).map_err(|err| err.in_file(path))
+}
+
+// parses the source text of a file, a shebang line and inner attributes are
+// accepted
+pub(crate) fn parse_source(source: &str) -> Result<syn::File> {
+    // the shebang is blanked out, so that the positions in the errors
+    // refer to the original source
+    let mut source = source.trim_start_matches('\u{feff}').to_string();
+    if source.starts_with("#!") && !source.starts_with("#![") {
+        let end = source.find('\n').unwrap_or(source.len());
+        source.replace_range(..end, &" ".repeat(end));
+    }
+
+    let tokens: TokenStream = source.parse()?;
+    Ok(syn::parse2(tokens)?)
+}
+
+// replaces every `mod foo;` declaration among the items with the
+// module loaded from its file
+fn load_modules(items: &mut [Item], dir: &ModuleDir) -> Result<()> {
+    for item in items {
+        if let Item::Mod(module) = item {
+            load_module(module, dir)?;
+        }
+    }
+    Ok(())
+}
+
+fn load_module(module: &mut ItemMod, dir: &ModuleDir) -> Result<()> {
+    let name = module.ident.to_string();
+    let name = name.trim_start_matches("r#");
+    let path_attr = path_attr(module);
+
+    if let Some((_, items)) = &mut module.content {
+        let inline_dir = match path_attr {
+            Some(path) => ModuleDir {
+                dir: dir.path_base.join(&path),
+                path_base: dir.path_base.join(&path),
+            },
+            None => dir.of_inline(name),
+        };
+        return load_modules(items, &inline_dir);
+    }
+
+    let (path, owns_dir) = match path_attr {
+        Some(path) => (dir.path_base.join(path), true),
+        None => {
+            let file = dir.dir.join(format!("{name}.rs"));
+            if file.is_file() {
+                (file, false)
+            } else {
+                (dir.dir.join(name).join("mod.rs"), true)
+            }
+        }
+    };
+
+    let mut file = read_file(&path)?;
+    load_modules(&mut file.items, &ModuleDir::of_file(&path, owns_dir))?;
+
+    module.attrs.extend(file.attrs);
+    module.content = Some((Default::default(), file.items));
+    module.semi = None;
+    Ok(())
+}
+
+// the value of the `#[path = "..."]` attribute of a module
+fn path_attr(module: &ItemMod) -> Option<String> {
+    module.attrs.iter().find_map(|attr| match &attr.meta {
+        Meta::NameValue(meta) if meta.path.is_ident("path") => match &meta.value {
+            syn::Expr::Lit(expr) => match &expr.lit {
+                Lit::Str(path) => Some(path.value()),
+                _ => None,
+            },
+            _ => None,
+        },
+        _ => None,
+    })
+}
@@ -0,0 +1,142 @@
+//! Imports of a `use` declaration.
+//!
+//! A `use` declaration can import several items with a single use tree, e.g.
+//! `use std::{fmt, io::Write as _};`. The tree is flattened into one
+//! [`Import`] for each of its leaves.
+
+use std::fmt::Display;
+use syn::{Ident, ItemUse, UseTree};
+
+/// A single import of a `use` declaration, e.g. `std::io::Write as _`
+/// or `std::collections::*`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Import {
+    /// The segments of the imported path. For a glob import this is the
+    /// path of the module whose items are imported.
+    path: Vec<Ident>,
+
+    /// The name the import is bound to with `as`.
+    alias: Option<Ident>,
+
+    /// Whether this is a glob (`*`) import.
+    glob: bool,
+
+    /// Whether the path starts with `::`.
+    leading_colon: bool,
+}
+
+impl Import {
+    /// Returns the flattened imports of a `use` declaration.
+    pub fn from_item_use(item_use: &ItemUse) -> Vec<Import> {
+        let mut imports = vec![];
+        let leading_colon = item_use.leading_colon.is_some();
+        flatten(&item_use.tree, &mut vec![], leading_colon, &mut imports);
+        imports
+    }
+
+    /// Returns the segments of the imported path.
+    pub fn get_path(&self) -> &[Ident] {
+        &self.path
+    }
+
+    /// Returns the name given to the import with `as`, if any.
+    pub fn get_alias(&self) -> Option<&Ident> {
+        self.alias.as_ref()
+    }
+
+    /// Returns the name the import is bound to in its scope or `None` for
+    /// a glob import.
+    pub fn get_name(&self) -> Option<&Ident> {
+        if self.glob {
+            return None;
+        }
+        self.alias.as_ref().or(self.path.last())
+    }
+
+    /// Returns `true` if this is a glob (`*`) import.
+    pub fn is_glob(&self) -> bool {
+        self.glob
+    }
+
+    /// Returns `true` if this import brings the item with the given path,
+    /// e.g. `"std::io::Write"`, in scope, either by name or with a glob.
+    pub fn imports(&self, path: &str) -> bool {
+        let segments = path
+            .trim_start_matches("::")
+            .split("::")
+            .collect::<Vec<&str>>();
+        let module = if self.glob {
+            &segments[..segments.len() - 1]
+        } else {
+            &segments[..]
+        };
+
+        module.len() == self.path.len()
+            && self
+                .path
+                .iter()
+                .zip(module)
+                .all(|(ident, segment)| ident == *segment)
+    }
+}
+
+// collects the leaves of the use tree, `prefix` holds the segments of the
+// path down to the current subtree
+fn flatten(
+    tree: &UseTree,
+    prefix: &mut Vec<Ident>,
+    leading_colon: bool,
+    imports: &mut Vec<Import>,
+) {
+    let mut push = |path: Vec<Ident>, alias: Option<Ident>, glob: bool| {
+        imports.push(Import {
+            path,
+            alias,
+            glob,
+            leading_colon,
+        })
+    };
+
+    match tree {
+        UseTree::Path(path) => {
+            prefix.push(path.ident.to_owned());
+            flatten(&path.tree, prefix, leading_colon, imports);
+            prefix.pop();
+        }
+        UseTree::Name(name) => push(import_path(prefix, &name.ident), None, false),
+        UseTree::Rename(rename) => {
+            let path = import_path(prefix, &rename.ident);
+            push(path, Some(rename.rename.to_owned()), false)
+        }
+        UseTree::Glob(_) => push(prefix.to_owned(), None, true),
+        UseTree::Group(group) => {
+            for tree in &group.items {
+                flatten(tree, prefix, leading_colon, imports);
+            }
+        }
+    }
+}
+
+// `self` in a group imports the module of the group itself
+fn import_path(prefix: &[Ident], ident: &Ident) -> Vec<Ident> {
+    let mut path = prefix.to_vec();
+    if ident != "self" || path.is_empty() {
+        path.push(ident.to_owned());
+    }
+    path
+}
+
+impl Display for Import {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let path = self.path.iter().map(Ident::to_string);
+        let path = path.collect::<Vec<String>>().join("::");
+        let colon = if self.leading_colon { "::" } else { "" };
+
+        match (&self.alias, self.glob) {
+            (_, true) if path.is_empty() => write!(f, "{colon}*"),
+            (_, true) => write!(f, "{colon}{path}::*"),
+            (Some(alias), false) => write!(f, "{colon}{path} as {alias}"),
+            (None, false) => write!(f, "{colon}{path}"),
+        }
+    }
+}
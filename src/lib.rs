@@ -16,11 +16,11 @@
 //!
 //! This crate is motivated by the need of having a parser with several properties:
 //! 1. Tree output. The parser should make available be a tree structure with parent-child
-//! relationships between the nodes,
-//! 1. No need for special handling of input code. The parser should ingest an arbitrary sequence
-//! of constructs and parse it automatically.
+//!    relationships between the nodes,
+//! 2. No need for special handling of input code. The parser should ingest an arbitrary sequence
+//!    of constructs and parse it automatically.
 //! 3. Easy traversal and extension. The tree should be easily extendable, printable, traversable
-//! and serializable.
+//!    and serializable.
 //!
 //! The [`syn`] crate offers many types (see e.g. [`syn::Item`] or [`syn::Expr`]) to parse code
 //! into, but parsing within the `Parse` trait can be laborious and [`ParseStream`] doesn't have a
@@ -45,6 +45,8 @@
 //! ## Examples
 //! ### Visualizing the tree
 //! ```
+//! # use quote::quote;
+//! # use rustree::speculative_parse;
 //! # fn main() {
 //! let tokens = quote! {
 //!     fn cube(x: i32) -> i32 {
@@ -59,6 +61,7 @@
 //! };
 //!
 //! let ast = speculative_parse(tokens).unwrap();
+//! # #[cfg(feature = "debug")]
 //! ast.print_tree();
 //! # }
 //! ```
@@ -66,12 +69,13 @@
 //! ```text
 //!level  id
 //!  0     0  node: Root, parent: 0, children: [1]
-//!  1     1 ── node: ItemFn: cube, parent: 0, children: [2, 4, 5, 6]
-//!  2     2 ──── node: ItemFn: square, parent: 1, children: [3]
+//!  1     1 ── node: ItemFn: cube, parent: 0, children: [2, 5, 6, 7]
+//!  2     2 ──── node: ItemFn: square, parent: 1, children: [3, 4]
 //!  3     3 ────── node: Stmt: Macro: "println", parent: 2, children: []
-//!  2     4 ──── node: Stmt: Local: result, parent: 1, children: []
-//!  2     5 ──── node: Stmt: Macro: "print", parent: 1, children: []
-//!  2     6 ──── node: ExprPath: result, parent: 1, children: []
+//!  3     4 ────── node: ExprBinary: Binary (tail), parent: 2, children: []
+//!  2     5 ──── node: Stmt: Local: result, parent: 1, children: []
+//!  2     6 ──── node: Stmt: Macro: "print", parent: 1, children: []
+//!  2     7 ──── node: ExprPath: result (tail), parent: 1, children: []
 //! ```
 //! where `parent` is the `id` of the parent and `children` are the `id`'s of the children
 //!
@@ -80,13 +84,24 @@
 //! modified code.
 
 pub mod ast;
-mod construct;
-mod node;
+pub mod construct;
+pub mod error;
+mod file;
+pub mod import;
+pub mod node;
 pub mod parse;
+pub mod select;
+#[cfg(feature = "serialize")]
+mod serialize;
+pub mod span;
+pub mod traverse;
 mod utils;
+pub mod visit;
 
 use ast::SyntaxTree;
+use error::Result;
 use parse::speculative_parse_inner;
+use std::path::Path;
 use syn::parse::Parser;
 
 /// Parsing function to be used in a macro. It parses a valid rust code and returns a [`SyntaxTree`] without
@@ -97,3 +112,27 @@ use syn::parse::Parser;
 pub fn speculative_parse(tokens: impl Into<proc_macro2::TokenStream>) -> syn::Result<SyntaxTree> {
     speculative_parse_inner.parse2(tokens.into())
 }
+
+/// Parses rust source code given as a string into a [`SyntaxTree`]. Unlike [`speculative_parse`]
+/// this function is meant to be used outside of a macro: the source is parsed as a whole file,
+/// with a shebang line and inner attributes (e.g. `//!` docs), and the returned
+/// [`Error`](error::Error) reports the line and column in the source where the parsing failed.
+pub fn parse_str(source: &str) -> Result<SyntaxTree> {
+    let file = file::parse_source(source)?;
+    Ok(parse::parse_file(&file))
+}
+
+/// Parses the rust source file at the given path into a [`SyntaxTree`]. A shebang line and
+/// the inner attributes of the file (e.g. `#![allow(dead_code)]`) are accepted. Modules declared
+/// with `mod foo;` are not loaded, see [`parse_crate`].
+pub fn parse_file(path: impl AsRef<Path>) -> Result<SyntaxTree> {
+    file::parse_file(path.as_ref())
+}
+
+/// Parses a whole crate into a single [`SyntaxTree`]. The `root` is either the root file of the
+/// crate (e.g. `src/lib.rs`) or the directory of the crate. Every `mod foo;` declaration is
+/// followed to `foo.rs`, `foo/mod.rs` or to the file given with a `#[path]` attribute and becomes
+/// a module node with the items of the file as children.
+pub fn parse_crate(root: impl AsRef<Path>) -> Result<SyntaxTree> {
+    file::parse_crate(root.as_ref())
+}
@@ -2,11 +2,19 @@
 //! Adding a new node to the tree should be handled with the api provided by
 //! `SyntaxTree`.
 
-use super::construct::Construct;
+use super::construct::{Construct, ConstructKind};
+use super::import::Import;
+use super::span::Position;
+#[cfg(feature = "debug")]
 use core::fmt::Debug;
-use proc_macro2::TokenStream;
+use core::fmt::Display;
+use proc_macro2::{Span, TokenStream};
 use quote::ToTokens;
-use syn::Ident;
+#[cfg(feature = "serialize")]
+use serde::{Deserialize, Serialize};
+use std::ops::Range;
+use syn::spanned::Spanned;
+use syn::{AngleBracketedGenericArguments, Attribute, Expr, Ident, Lifetime, Pat, Visibility};
 
 #[cfg_attr(feature = "serialize", derive(Deserialize, Serialize))]
 #[derive(Clone)]
@@ -17,6 +25,7 @@ pub struct Node {
     pub(crate) id: usize,
 
     /// The name of the construct.
+    #[cfg_attr(feature = "serialize", serde(with = "crate::serialize::ident"))]
     pub(crate) ident: Ident, // TODO
 
     /// A [`syn`] type (e.g. a [`syn::ItemStruct`])
@@ -30,17 +39,37 @@ pub struct Node {
 
     /// The level of this node in the tree.
     pub(crate) level: usize,
+
+    /// Whether the statement this node was parsed from ends with a semicolon.
+    pub(crate) semi: bool,
+
+    /// Whether the semicolon was added because a statement was inserted
+    /// after this one, rather than parsed.
+    pub(crate) semi_added: bool,
+
+    /// Whether this node is the tail expression of the block it was parsed
+    /// from, i.e. the value the block evaluates to.
+    pub(crate) tail: bool,
+
+    /// The span of the source code the construct was parsed from.
+    #[cfg_attr(feature = "serialize", serde(skip, default = "Span::call_site"))]
+    pub(crate) span: Span,
 }
 
 impl Node {
     pub(crate) fn new(data: Construct, id: usize, ident: Ident) -> Node {
+        let span = data.span();
         Node {
-            id: id,
-            ident: ident,
-            data: data,
+            id,
+            ident,
+            data,
             parent: 0,
             children: vec![],
             level: 0,
+            semi: false,
+            semi_added: false,
+            tail: false,
+            span,
         }
     }
 
@@ -53,6 +82,176 @@ impl Node {
     pub fn get_level(&self) -> &usize {
         &self.level
     }
+
+    /// Returns a reference to the name (identifier) of the node.
+    pub fn get_ident(&self) -> &Ident {
+        &self.ident
+    }
+
+    /// Returns a reference to the [`Construct`] this node represents.
+    pub fn get_construct(&self) -> &Construct {
+        &self.data
+    }
+
+    /// Returns the kind of the [`Construct`] this node represents.
+    pub fn get_kind(&self) -> ConstructKind {
+        self.data.kind()
+    }
+
+    /// Returns a mutable reference to the [`Construct`] this node represents.
+    /// Changes to the construct are part of the output of
+    /// [`SyntaxTree::get_tokenstream`](crate::ast::SyntaxTree::get_tokenstream),
+    /// except for the parts that are rebuilt from the children of the node,
+    /// e.g. the statements of a function body or the methods of an `impl`
+    /// block. Those are changed by editing the children or by replacing the
+    /// node with [`SyntaxTree::replace_node`](crate::ast::SyntaxTree::replace_node).
+    pub fn get_construct_mut(&mut self) -> &mut Construct {
+        &mut self.data
+    }
+
+    /// Returns a reference to the id of the parent of the node.
+    /// The parent of the root node is the root node itself.
+    pub fn get_parent(&self) -> &usize {
+        &self.parent
+    }
+
+    /// Returns the ids of the children of the node in source order.
+    pub fn get_children(&self) -> &[usize] {
+        &self.children
+    }
+
+    /// Returns the outer attributes (e.g. `#[inline]`) of the construct.
+    pub fn get_attributes(&self) -> &[Attribute] {
+        self.data.attrs()
+    }
+
+    /// Returns `true` if the construct has an attribute with the given path,
+    /// e.g. `"cfg"` or `"tokio::test"`.
+    pub fn has_attribute(&self, path: &str) -> bool {
+        self.get_attributes().iter().any(|attr| {
+            let segments = attr.path().segments.iter();
+            let name = segments
+                .map(|segment| segment.ident.to_string())
+                .collect::<Vec<String>>()
+                .join("::");
+            name == path
+        })
+    }
+
+    /// Returns the visibility of the construct or `None` if the construct
+    /// cannot have a visibility (e.g. a statement).
+    pub fn get_visibility(&self) -> Option<&Visibility> {
+        self.data.vis()
+    }
+
+    /// Returns the label of a loop or block (e.g. `'outer`), or `None` if the
+    /// construct has no label.
+    pub fn get_label(&self) -> Option<&Lifetime> {
+        self.data.label()
+    }
+
+    /// Returns `true` if the statement of the node ends with a semicolon.
+    pub fn has_semi(&self) -> bool {
+        self.semi
+    }
+
+    /// Returns `true` if the node is the tail expression of a block, i.e. the
+    /// last statement without a semicolon whose value the block evaluates to.
+    /// The body of a closure or `match` arm without braces is a tail as well.
+    pub fn is_tail(&self) -> bool {
+        self.tail
+    }
+
+    /// Returns `true` if the node is a `move` closure or an `async move` block.
+    pub fn is_move(&self) -> bool {
+        match &self.data {
+            Construct::ExprClosure(closure) => closure.capture.is_some(),
+            Construct::ExprAsync(block) => block.capture.is_some(),
+            _ => false,
+        }
+    }
+
+    /// Returns the parameters of a closure, or an empty vector if the node is
+    /// not a closure.
+    pub fn get_params(&self) -> Vec<&Pat> {
+        match &self.data {
+            Construct::ExprClosure(closure) => closure.inputs.iter().collect(),
+            _ => vec![],
+        }
+    }
+
+    /// Returns the arguments of a function call or of a method call of a
+    /// chain, or an empty vector if the node is not a call.
+    pub fn get_args(&self) -> Vec<&Expr> {
+        match &self.data {
+            Construct::ExprCall(call) => call.args.iter().collect(),
+            Construct::Method(call) => call.args.iter().collect(),
+            _ => vec![],
+        }
+    }
+
+    /// Returns the generic arguments of a method call given with the
+    /// turbofish, e.g. `<Vec<_>>` in `.collect::<Vec<_>>()`.
+    pub fn get_turbofish(&self) -> Option<&AngleBracketedGenericArguments> {
+        match &self.data {
+            Construct::Method(call) => call.turbofish.as_ref(),
+            _ => None,
+        }
+    }
+
+    /// Returns the imports of a `use` declaration, one for each leaf of the
+    /// use tree, or an empty vector if this node is not a `use` declaration.
+    pub fn get_imports(&self) -> Vec<Import> {
+        match &self.data {
+            Construct::ItemUse(item_use) => Import::from_item_use(item_use),
+            _ => vec![],
+        }
+    }
+
+    /// Returns the span of the source code the construct was parsed from.
+    /// Inside a procedural macro on a stable compiler the span covers
+    /// only the first token of the construct.
+    pub fn get_span(&self) -> Span {
+        self.span
+    }
+
+    /// Returns the position in the source code where the construct starts.
+    pub fn get_start(&self) -> Position {
+        Position::start_of(self.span)
+    }
+
+    /// Returns the position in the source code right after the construct.
+    pub fn get_end(&self) -> Position {
+        Position::end_of(self.span)
+    }
+
+    /// Returns the byte range of the construct in the source code.
+    pub fn get_byte_range(&self) -> Range<usize> {
+        self.span.byte_range()
+    }
+
+    /// Returns `true` if the construct covers the given position, with a
+    /// 1-indexed line and a 0-indexed column.
+    pub fn contains(&self, line: usize, column: usize) -> bool {
+        let (start, end) = (self.get_start(), self.get_end());
+        (start.line, start.column) <= (line, column) && (line, column) < (end.line, end.column)
+    }
+
+    /// Returns an error spanning the construct, e.g. to report it with
+    /// [`syn::Error::to_compile_error`] from a procedural macro.
+    pub fn error<T: Display>(&self, message: T) -> syn::Error {
+        syn::Error::new_spanned(&self.data, message)
+    }
+
+    /// Returns `true` if this node is the root of the tree.
+    pub fn is_root(&self) -> bool {
+        matches!(self.data, Construct::Root)
+    }
+
+    /// Returns `true` if this node has no children.
+    pub fn is_leaf(&self) -> bool {
+        self.children.is_empty()
+    }
 }
 
 impl ToTokens for Node {
@@ -68,8 +267,14 @@ impl Debug for Node {
 
         write!(
             f,
-            "{:^5} {:>3} {} node: {:?}, parent: {}, children: {:?}",
-            self.level, self.id, under, self.data, self.parent, self.children
+            "{:^5} {:>3} {} node: {:?}{}, parent: {}, children: {:?}",
+            self.level,
+            self.id,
+            under,
+            self.data,
+            if self.tail { " (tail)" } else { "" },
+            self.parent,
+            self.children
         )
     }
 }
@@ -1,6 +1,7 @@
 use super::ast::SyntaxTree;
 use super::construct::Construct;
-use syn::parse::{Parse, ParseStream};
+use super::error::NO_PROGRESS;
+use syn::parse::{Parse, ParseStream, Peek};
 use syn::visit::Visit;
 
 pub(crate) fn speculative_parse_inner(stream: ParseStream) -> syn::Result<SyntaxTree> {
@@ -8,17 +9,77 @@ pub(crate) fn speculative_parse_inner(stream: ParseStream) -> syn::Result<Syntax
     parse_inner(ast, &stream)
 }
 
+// builds the tree of an already parsed file from its inner attributes and
+// its items
+pub(crate) fn parse_file(file: &syn::File) -> SyntaxTree {
+    let mut tree = SyntaxTree::new();
+    tree.set_attributes(file.attrs.to_owned());
+    let items = &file.items;
+    for item in items {
+        tree.set_current_level(1);
+        tree.visit_item(item);
+        tree.set_current_level(0);
+    }
+    tree
+}
+
 pub(crate) fn parse_construct<T: Parse>(stream: ParseStream) -> syn::Result<T> {
     T::parse(stream)
 }
 
+// looks ahead past the attributes, the visibility and the qualifiers of an
+// item (e.g. `#[inline] pub(crate) const unsafe fn`) and checks whether the
+// item starts with the given keyword
+pub(crate) fn peek_item(stream: ParseStream, keyword: impl Peek) -> bool {
+    let ahead = stream.fork();
+    skip_item_prefix(&ahead).is_ok() && ahead.peek(keyword)
+}
+
+fn skip_item_prefix(stream: ParseStream) -> syn::Result<()> {
+    stream.call(syn::Attribute::parse_outer)?;
+    stream.parse::<syn::Visibility>()?;
+
+    loop {
+        if stream.peek(syn::Token![const]) {
+            // `const` is a qualifier of a function, otherwise it starts a constant
+            let qualifies_fn = stream.peek2(syn::Token![fn])
+                || stream.peek2(syn::Token![unsafe])
+                || stream.peek2(syn::Token![async])
+                || stream.peek2(syn::Token![extern]);
+            if !qualifies_fn {
+                break;
+            }
+            stream.parse::<syn::Token![const]>()?;
+        } else if stream.peek(syn::Token![async])
+            || stream.peek(syn::Token![unsafe])
+            || stream.peek(syn::Token![default])
+            || stream.peek(syn::Token![auto])
+        {
+            stream.parse::<proc_macro2::TokenTree>()?;
+        } else if stream.peek(syn::Token![extern]) && !stream.peek2(syn::Token![crate]) {
+            stream.parse::<syn::Abi>()?;
+        } else {
+            break;
+        }
+    }
+
+    Ok(())
+}
+
+// a macro invocation such as `name! (...)`, but not a `macro_rules! name {...}`
+// definition which is parsed as an item
+pub(crate) fn is_macro(stream: ParseStream) -> bool {
+    stream.peek(syn::Ident) && stream.peek2(syn::Token![!]) && !stream.peek3(syn::Ident)
+}
+
 macro_rules! parse_inner_macro {
     () => {};
     ($($name:ident $typ:ident $fn_name:ident)+) => {
         fn parse_inner(mut tree: SyntaxTree, stream: &ParseStream) -> syn::Result<SyntaxTree> {
             while !stream.cursor().eof() {
+                let cursor = stream.cursor();
                 $(
-                    if stream.peek(syn::Token![$name]) || stream.peek2(syn::Token![$name]){
+                    if peek_item(stream, syn::Token![$name]) {
                         let child = Construct::$typ(parse_construct(stream)?);
                         let c: syn::$typ = child.try_into()?;
                         tree.set_current_level(1);
@@ -26,13 +87,28 @@ macro_rules! parse_inner_macro {
                         tree.set_current_level(0);
                     };
                 )*
-                if stream.peek(syn::Ident) && stream.peek2(syn::Token![!]) {
+                if is_macro(stream) {
                     let child = Construct::Macro(parse_construct(stream)?);
                     let c: syn::Macro = child.try_into()?;
+                    let _: Option<syn::Token![;]> = stream.parse()?;
                     tree.set_current_level(1);
                     tree.visit_macro(&c);
                     tree.set_current_level(0);
                 }
+
+                // any other item is parsed generically, the parsing errors
+                // at the first token that does not start an item
+                if stream.cursor() == cursor {
+                    let item: syn::Item = parse_construct(stream)?;
+                    tree.set_current_level(1);
+                    tree.visit_item(&item);
+                    tree.set_current_level(0);
+                }
+
+                // guarantees that the loop always makes progress
+                if stream.cursor() == cursor {
+                    return Err(stream.error(NO_PROGRESS));
+                }
             }
             Ok(tree)
         }
@@ -46,4 +122,11 @@ parse_inner_macro!(
     fn      ItemFn      visit_item_fn
     impl    ItemImpl    visit_item_impl
     trait   ItemTrait   visit_item_trait
+    enum    ItemEnum    visit_item_enum
+    union   ItemUnion   visit_item_union
+    const   ItemConst   visit_item_const
+    static  ItemStatic  visit_item_static
+    type    ItemType    visit_item_type
+    mod     ItemMod     visit_item_mod
+    use     ItemUse     visit_item_use
 );
@@ -0,0 +1,428 @@
+//! Selectors that query the nodes of a [`SyntaxTree`] by kind, name and
+//! ancestry, in the style of CSS selectors.
+//!
+//! A selector is a sequence of compound selectors separated by combinators:
+//!
+//! * `fn` matches the nodes of a kind of construct, `*` matches any node,
+//! * `[name=square]` matches a property of the node, `[trait]` matches the
+//!   nodes that have the property. Values can be quoted, e.g. `[name="r#fn"]`,
+//! * `a b` matches the nodes matched by `b` that have an ancestor matched by `a`,
+//! * `a > b` matches the nodes matched by `b` whose parent is matched by `a`,
+//! * `a, b` matches the nodes matched by either `a` or `b`.
+//!
+//! For example `impl[trait=Operations] > fn[name=square] macro[name=println]`
+//! matches the `println!` calls in the `square` method of an implementation of
+//! `Operations`.
+//!
+//! The kinds are `struct`, `fn` (functions and methods), `impl`, `trait`, `enum`,
+//! `union`, `const`, `static`, `type`, `mod`, `use`, `variant`, `field`, `path`,
+//! `macro`, `let`, `lit`, `assign`, `return`, `if`, `match`, `loop` (`loop`,
+//! `while` and `for`), `branch` (the `then` and `else` branches of an `if`),
+//! `arm` (the arms of a `match`), `call` (function calls and chains of method
+//! calls), `method` (a method call of a chain, e.g. `method[name=unwrap]`),
+//! `closure`, `async` (`async` blocks), `expr` (any other expression), `item`
+//! and `root`. The name of a [`ConstructKind`]
+//! (e.g. `impl_item_fn`) matches only the nodes of that kind, unless it is
+//! also one of the names above: `macro`, `method`, `item` and `root` always
+//! name the broader kind, e.g. `macro` matches the nodes of
+//! [`ConstructKind::StmtMacro`] as well as those of [`ConstructKind::Macro`].
+//!
+//! The properties are `name` (the name of the node), `trait` (the trait of an
+//! `impl`), `type` (the type of an `impl`), `attr` (the path of an attribute of
+//! the construct), `vis` (the visibility of the construct, e.g. `pub(crate)`)
+//! and `label` (the label of a loop without the `'`, e.g. `outer`).
+
+use crate::ast::SyntaxTree;
+use crate::construct::{Construct, ConstructKind};
+use crate::error::{Error, Result};
+use crate::node::Node;
+use quote::ToTokens;
+use std::str::FromStr;
+use syn::{Stmt, Type};
+
+const KINDS: &[&str] = &[
+    "struct", "fn", "impl", "trait", "enum", "union", "const", "static", "type", "mod", "use",
+    "variant", "field", "path", "macro", "let", "lit", "assign", "return", "if", "match", "loop",
+    "branch", "arm", "call", "method", "closure", "async", "expr", "item", "root",
+];
+
+const PROPERTIES: &[&str] = &["name", "trait", "type", "attr", "vis", "label"];
+
+/// A parsed selector, see the [module documentation](self) for the syntax.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Selector {
+    /// The alternatives separated by commas.
+    alternatives: Vec<Complex>,
+}
+
+// compound selectors with the combinators that relate them, the first
+// combinator is always `Descendant`
+type Complex = Vec<(Combinator, Compound)>;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Combinator {
+    Descendant,
+    Child,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct Compound {
+    kind: Option<Kind>,
+    filters: Vec<Filter>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum Kind {
+    /// One of the short names, e.g. `fn`, that can match several kinds.
+    Category(String),
+    Construct(ConstructKind),
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct Filter {
+    property: String,
+    value: Option<String>,
+}
+
+impl Selector {
+    /// Parses a selector string.
+    pub fn parse(selector: &str) -> Result<Selector> {
+        Parser {
+            selector,
+            offset: 0,
+        }
+        .parse()
+    }
+
+    /// Returns `true` if the node with the given id is matched by the selector.
+    pub fn matches(&self, tree: &SyntaxTree, id: usize) -> bool {
+        let Some(node) = tree.get_node(id) else {
+            return false;
+        };
+        self.alternatives
+            .iter()
+            .any(|complex| matches_complex(tree, node, complex))
+    }
+}
+
+impl FromStr for Selector {
+    type Err = Error;
+
+    fn from_str(selector: &str) -> Result<Selector> {
+        Selector::parse(selector)
+    }
+}
+
+// matches the node against the last compound selector and its ancestors
+// against the preceding ones
+fn matches_complex(tree: &SyntaxTree, node: &Node, complex: &[(Combinator, Compound)]) -> bool {
+    let Some(((combinator, compound), rest)) = complex.split_last() else {
+        return true;
+    };
+    if !compound.matches(node) {
+        return false;
+    }
+    if rest.is_empty() {
+        return true;
+    }
+
+    match combinator {
+        Combinator::Child => tree
+            .ancestors(node.id)
+            .next()
+            .is_some_and(|parent| matches_complex(tree, parent, rest)),
+        Combinator::Descendant => tree
+            .ancestors(node.id)
+            .any(|ancestor| matches_complex(tree, ancestor, rest)),
+    }
+}
+
+impl Compound {
+    fn matches(&self, node: &Node) -> bool {
+        let kind = self.kind.as_ref().is_none_or(|kind| match kind {
+            Kind::Category(category) => category == kind_name(&node.data),
+            Kind::Construct(kind) => *kind == node.get_kind(),
+        });
+        kind && self.filters.iter().all(|filter| filter.matches(node))
+    }
+}
+
+impl Filter {
+    fn matches(&self, node: &Node) -> bool {
+        let value = self.value.as_deref();
+        let matches = |actual: String| value.is_none_or(|value| value == actual);
+
+        match self.property.as_str() {
+            "name" => matches(node.ident.to_string()),
+            "trait" => match &node.data {
+                Construct::ItemImpl(item) => item
+                    .trait_
+                    .as_ref()
+                    .is_some_and(|(_, path, _)| path_matches(path, value)),
+                _ => false,
+            },
+            "type" => match &node.data {
+                Construct::ItemImpl(item) => match item.self_ty.as_ref() {
+                    Type::Path(path) => path_matches(&path.path, value),
+                    typ => matches(typ.to_token_stream().to_string()),
+                },
+                _ => false,
+            },
+            "attr" => match value {
+                Some(value) => node.has_attribute(value),
+                None => !node.get_attributes().is_empty(),
+            },
+            "vis" => node.get_visibility().is_some_and(|vis| {
+                let vis = vis.to_token_stream().to_string().replace(' ', "");
+                !vis.is_empty() && matches(vis)
+            }),
+            "label" => node
+                .get_label()
+                .is_some_and(|label| matches(label.ident.to_string())),
+            _ => false,
+        }
+    }
+}
+
+// a path matches either with all its segments (e.g. `std::fmt::Display`)
+// or with its last segment (e.g. `Display`)
+fn path_matches(path: &syn::Path, value: Option<&str>) -> bool {
+    let Some(value) = value else {
+        return true;
+    };
+    let segments = path
+        .segments
+        .iter()
+        .map(|segment| segment.ident.to_string());
+    let segments = segments.collect::<Vec<String>>();
+    segments.join("::") == value || segments.last().is_some_and(|last| last == value)
+}
+
+// the name of the kind of the construct used in the selectors
+fn kind_name(construct: &Construct) -> &'static str {
+    match construct {
+        Construct::ItemStruct(_) => "struct",
+        Construct::ItemFn(_) | Construct::ImplItemFn(_) | Construct::TraitItemFn(_) => "fn",
+        Construct::ItemImpl(_) => "impl",
+        Construct::ItemTrait(_) => "trait",
+        Construct::ItemEnum(_) => "enum",
+        Construct::ItemUnion(_) => "union",
+        Construct::ItemConst(_) => "const",
+        Construct::ItemStatic(_) => "static",
+        Construct::ItemType(_) => "type",
+        Construct::ItemMod(_) => "mod",
+        Construct::ItemUse(_) => "use",
+        Construct::Variant(_) => "variant",
+        Construct::Field(_) => "field",
+        Construct::ExprPath(_) => "path",
+        Construct::ExprMacro(_) | Construct::StmtMacro(_) | Construct::Macro(_) => "macro",
+        Construct::ExprLet(_) | Construct::Local(_) => "let",
+        Construct::ExprLit(_) => "lit",
+        Construct::ExprAssign(_) => "assign",
+        Construct::ExprReturn(_) => "return",
+        Construct::ExprIf(_) => "if",
+        Construct::ExprMatch(_) => "match",
+        Construct::ExprLoop(_) | Construct::ExprWhile(_) | Construct::ExprForLoop(_) => "loop",
+        Construct::ExprCall(_) | Construct::ExprMethodCall(_) => "call",
+        Construct::ExprClosure(_) => "closure",
+        Construct::ExprAsync(_) => "async",
+        Construct::Block(_) => "branch",
+        Construct::Arm(_) => "arm",
+        Construct::Method(_) => "method",
+        Construct::ExprArray(_)
+        | Construct::ExprAwait(_)
+        | Construct::ExprBinary(_)
+        | Construct::ExprBlock(_)
+        | Construct::ExprBreak(_)
+        | Construct::ExprCast(_)
+        | Construct::ExprConst(_)
+        | Construct::ExprContinue(_)
+        | Construct::ExprField(_)
+        | Construct::ExprGroup(_)
+        | Construct::ExprIndex(_)
+        | Construct::ExprInfer(_)
+        | Construct::ExprParen(_)
+        | Construct::ExprRange(_)
+        | Construct::ExprRawAddr(_)
+        | Construct::ExprReference(_)
+        | Construct::ExprRepeat(_)
+        | Construct::ExprStruct(_)
+        | Construct::ExprTry(_)
+        | Construct::ExprTryBlock(_)
+        | Construct::ExprTuple(_)
+        | Construct::ExprUnary(_)
+        | Construct::ExprUnsafe(_)
+        | Construct::ExprYield(_) => "expr",
+        Construct::Stmt(Stmt::Local(_)) => "let",
+        Construct::Stmt(Stmt::Macro(_)) => "macro",
+        Construct::Stmt(Stmt::Item(_)) | Construct::Item(_) => "item",
+        Construct::Stmt(Stmt::Expr(..)) => "expr",
+        Construct::Root => "root",
+        Construct::None => "",
+    }
+}
+
+struct Parser<'a> {
+    selector: &'a str,
+    offset: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn parse(mut self) -> Result<Selector> {
+        let mut alternatives = vec![self.complex()?];
+        while self.eat(',') {
+            alternatives.push(self.complex()?);
+        }
+
+        self.skip_whitespace();
+        match self.peek() {
+            None => Ok(Selector { alternatives }),
+            Some(c) => Err(self.error(format!("unexpected `{c}`"))),
+        }
+    }
+
+    fn complex(&mut self) -> Result<Complex> {
+        self.skip_whitespace();
+        let mut complex = vec![(Combinator::Descendant, self.compound()?)];
+
+        loop {
+            let whitespace = self.skip_whitespace();
+            let combinator = if self.eat('>') {
+                self.skip_whitespace();
+                Combinator::Child
+            } else if whitespace && self.peek().is_some_and(|c| c != ',') {
+                Combinator::Descendant
+            } else {
+                return Ok(complex);
+            };
+            complex.push((combinator, self.compound()?));
+        }
+    }
+
+    fn compound(&mut self) -> Result<Compound> {
+        let start = self.offset;
+        let kind = if self.eat('*') {
+            None
+        } else {
+            match self.word() {
+                "" => None,
+                kind if KINDS.contains(&kind) => Some(Kind::Category(kind.to_string())),
+                kind => match kind.parse() {
+                    Ok(kind) => Some(Kind::Construct(kind)),
+                    Err(_) => {
+                        let message = format!(
+                            "unknown kind `{kind}`, expected a construct kind or one of {}",
+                            KINDS.join(", ")
+                        );
+                        return Err(self.error_at(start, message));
+                    }
+                },
+            }
+        };
+
+        let mut filters = vec![];
+        while self.eat('[') {
+            filters.push(self.filter()?);
+        }
+
+        if self.offset == start {
+            return Err(self.error("expected a kind, `*` or `[`".to_string()));
+        }
+        Ok(Compound { kind, filters })
+    }
+
+    fn filter(&mut self) -> Result<Filter> {
+        self.skip_whitespace();
+        let start = self.offset;
+        let property = self.word();
+        if !PROPERTIES.contains(&property) {
+            let message = format!(
+                "unknown property `{property}`, expected one of {}",
+                PROPERTIES.join(", ")
+            );
+            return Err(self.error_at(start, message));
+        }
+
+        self.skip_whitespace();
+        let value = if self.eat('=') {
+            self.skip_whitespace();
+            Some(self.value()?)
+        } else {
+            None
+        };
+
+        self.skip_whitespace();
+        if !self.eat(']') {
+            return Err(self.error("expected `]`".to_string()));
+        }
+        Ok(Filter {
+            property: property.to_string(),
+            value,
+        })
+    }
+
+    fn value(&mut self) -> Result<String> {
+        if self.eat('"') {
+            let start = self.offset;
+            let Some(len) = self.rest().find('"') else {
+                return Err(self.error_at(start - 1, "unterminated string".to_string()));
+            };
+            self.offset += len + 1;
+            return Ok(self.selector[start..start + len].to_string());
+        }
+
+        let len = self
+            .rest()
+            .find(|c: char| c == ']' || c == '"' || c.is_whitespace())
+            .unwrap_or(self.rest().len());
+        if len == 0 {
+            return Err(self.error("expected a value".to_string()));
+        }
+        self.offset += len;
+        Ok(self.selector[self.offset - len..self.offset].to_string())
+    }
+
+    fn word(&mut self) -> &'a str {
+        let len = self
+            .rest()
+            .find(|c: char| !(c.is_alphanumeric() || c == '_'))
+            .unwrap_or(self.rest().len());
+        self.offset += len;
+        &self.selector[self.offset - len..self.offset]
+    }
+
+    // returns `true` if any whitespace was skipped
+    fn skip_whitespace(&mut self) -> bool {
+        let len = self.rest().len() - self.rest().trim_start().len();
+        self.offset += len;
+        len > 0
+    }
+
+    fn eat(&mut self, c: char) -> bool {
+        let eaten = self.peek() == Some(c);
+        if eaten {
+            self.offset += c.len_utf8();
+        }
+        eaten
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.rest().chars().next()
+    }
+
+    fn rest(&self) -> &'a str {
+        &self.selector[self.offset..]
+    }
+
+    fn error(&self, message: String) -> Error {
+        self.error_at(self.offset, message)
+    }
+
+    fn error_at(&self, offset: usize, message: String) -> Error {
+        Error::InvalidSelector {
+            selector: self.selector.to_string(),
+            message,
+            offset,
+        }
+    }
+}
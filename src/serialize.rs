@@ -0,0 +1,214 @@
+//! Serialization of the [`SyntaxTree`](crate::ast::SyntaxTree).
+//!
+//! [`syn`] types are not serializable, so every [`Construct`] is stored as
+//! its source text together with the name of its [`ConstructKind`].
+//! Deserializing a construct parses the source text back into the type of
+//! its kind.
+
+use crate::construct::{Construct, ConstructKind};
+use proc_macro2::Span;
+use quote::ToTokens;
+use serde::de::Error;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use syn::ext::IdentExt;
+use syn::parse::Parser;
+use syn::{Block, ExprGroup, Field, Ident, Stmt};
+
+/// The serialized form of a [`Construct`].
+#[derive(Deserialize, Serialize)]
+struct ConstructRepr {
+    kind: ConstructKind,
+    source: String,
+}
+
+macro_rules! construct_repr {
+    () => {};
+    ($($typ:ident)*) => {
+        impl From<&Construct> for ConstructRepr {
+            fn from(construct: &Construct) -> Self {
+                ConstructRepr {
+                    kind: construct.kind(),
+                    source: construct.to_token_stream().to_string(),
+                }
+            }
+        }
+
+        impl TryFrom<ConstructRepr> for Construct {
+            type Error = syn::Error;
+
+            fn try_from(repr: ConstructRepr) -> syn::Result<Self> {
+                let source = repr.source.as_str();
+                let construct = match repr.kind {
+                    $(
+                        ConstructKind::$typ => Construct::$typ(syn::parse_str(source)?),
+                    )*
+                    // parsed as statements since a tail expression has no semicolon
+                    ConstructKind::Stmt => Construct::Stmt(parse_stmt(source)?),
+                    ConstructKind::Local => match parse_stmt(source)? {
+                        Stmt::Local(local) => Construct::Local(local),
+                        _ => return Err(syn::Error::new(Span::call_site(), "expected `let`")),
+                    },
+                    ConstructKind::StmtMacro => match parse_stmt(source)? {
+                        Stmt::Macro(mac) => Construct::StmtMacro(mac),
+                        _ => return Err(syn::Error::new(Span::call_site(), "expected a macro")),
+                    },
+                    ConstructKind::Field => Construct::Field(
+                        Field::parse_named
+                            .parse_str(source)
+                            .or_else(|_| Field::parse_unnamed.parse_str(source))?,
+                    ),
+                    // the invisible delimiters of a group are not part of the source
+                    ConstructKind::ExprGroup => Construct::ExprGroup(ExprGroup {
+                        attrs: vec![],
+                        group_token: Default::default(),
+                        expr: Box::new(syn::parse_str(source)?),
+                    }),
+                    ConstructKind::Method => Construct::Method(syn::parse_str(source)?),
+                    ConstructKind::Root => Construct::Root,
+                    ConstructKind::None => Construct::None,
+                };
+                Ok(construct)
+            }
+        }
+    };
+}
+
+construct_repr!(
+    ItemStruct
+    ItemFn
+    ItemImpl
+    ItemTrait
+    ItemEnum
+    ItemUnion
+    ItemConst
+    ItemStatic
+    ItemType
+    ItemMod
+    ItemUse
+    Variant
+    TraitItemFn
+    ImplItemFn
+    ExprPath
+    ExprLet
+    ExprLit
+    ExprAssign
+    ExprReturn
+    ExprArray
+    ExprAsync
+    ExprAwait
+    ExprBinary
+    ExprBlock
+    ExprBreak
+    ExprCall
+    ExprCast
+    ExprClosure
+    ExprConst
+    ExprContinue
+    ExprField
+    ExprForLoop
+    ExprIf
+    ExprIndex
+    ExprInfer
+    ExprLoop
+    ExprMatch
+    ExprMethodCall
+    ExprParen
+    ExprRange
+    ExprRawAddr
+    ExprReference
+    ExprRepeat
+    ExprStruct
+    ExprTry
+    ExprTryBlock
+    ExprTuple
+    ExprUnary
+    ExprUnsafe
+    ExprWhile
+    ExprYield
+    Block
+    Arm
+    Macro
+    ExprMacro
+    Item
+);
+
+// parses a single statement of a block
+fn parse_stmt(source: &str) -> syn::Result<Stmt> {
+    let mut stmts = Block::parse_within.parse_str(source)?;
+    match (stmts.pop(), stmts.is_empty()) {
+        (Some(stmt), true) => Ok(stmt),
+        _ => Err(syn::Error::new(
+            Span::call_site(),
+            "expected a single statement",
+        )),
+    }
+}
+
+impl Serialize for Construct {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        ConstructRepr::from(self).serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Construct {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let repr = ConstructRepr::deserialize(deserializer)?;
+        Construct::try_from(repr).map_err(D::Error::custom)
+    }
+}
+
+impl Serialize for ConstructKind {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.name().serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for ConstructKind {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let name = String::deserialize(deserializer)?;
+        name.parse().map_err(D::Error::custom)
+    }
+}
+
+/// Serializes inner attributes as their source text. Used with
+/// `#[serde(with = "...")]`.
+pub(crate) mod attrs {
+    use super::*;
+    use syn::Attribute;
+
+    pub(crate) fn serialize<S: Serializer>(
+        attrs: &[Attribute],
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        let tokens = attrs.iter().map(|attr| attr.to_token_stream());
+        quote::quote!(#(#tokens)*).to_string().serialize(serializer)
+    }
+
+    pub(crate) fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Vec<Attribute>, D::Error> {
+        let source = String::deserialize(deserializer)?;
+        Attribute::parse_inner
+            .parse_str(&source)
+            .map_err(D::Error::custom)
+    }
+}
+
+/// Serializes an [`Ident`] as a string. Used with `#[serde(with = "...")]`.
+pub(crate) mod ident {
+    use super::*;
+
+    pub(crate) fn serialize<S: Serializer>(
+        ident: &Ident,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        ident.to_string().serialize(serializer)
+    }
+
+    pub(crate) fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Ident, D::Error> {
+        let name = String::deserialize(deserializer)?;
+        Ident::parse_any.parse_str(&name).map_err(D::Error::custom)
+    }
+}
@@ -0,0 +1,49 @@
+//! Positions of the nodes in the source code.
+//!
+//! Positions are available when the parsing is performed outside of a
+//! procedural macro (e.g. with [`parse_str`](crate::parse_str)) or in a
+//! procedural macro compiled with a toolchain that provides span locations.
+
+use proc_macro2::Span;
+use std::fmt::Display;
+
+/// A position in the source text.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Position {
+    /// The 1-indexed line.
+    pub line: usize,
+
+    /// The 0-indexed column in UTF-8 characters.
+    pub column: usize,
+
+    /// The 0-indexed byte offset.
+    pub offset: usize,
+}
+
+impl Position {
+    /// Returns the position of the start of the given span.
+    pub fn start_of(span: Span) -> Position {
+        let start = span.start();
+        Position {
+            line: start.line,
+            column: start.column,
+            offset: span.byte_range().start,
+        }
+    }
+
+    /// Returns the position of the end of the given span.
+    pub fn end_of(span: Span) -> Position {
+        let end = span.end();
+        Position {
+            line: end.line,
+            column: end.column,
+            offset: span.byte_range().end,
+        }
+    }
+}
+
+impl Display for Position {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}:{}", self.line, self.column + 1)
+    }
+}
@@ -0,0 +1,164 @@
+//! Iterators that traverse a [`SyntaxTree`] following the links between the
+//! nodes, so that the nodes are visited in source order also after the tree
+//! has been edited.
+
+use crate::ast::SyntaxTree;
+use crate::node::Node;
+use std::collections::VecDeque;
+
+/// An event of a depth-first traversal of the tree.
+#[derive(Clone, Copy)]
+pub enum Event<'a> {
+    /// The traversal reached the node, its descendants follow.
+    Enter(&'a Node),
+
+    /// The traversal visited all the descendants of the node.
+    Leave(&'a Node),
+}
+
+impl<'a> Event<'a> {
+    /// Returns the node of the event.
+    pub fn node(&self) -> &'a Node {
+        match self {
+            Event::Enter(node) | Event::Leave(node) => node,
+        }
+    }
+}
+
+/// Iterator over the nodes of a subtree in depth-first pre-order, i.e. every
+/// node comes before its children.
+pub struct Preorder<'a> {
+    tree: &'a SyntaxTree,
+    stack: Vec<usize>,
+}
+
+impl<'a> Preorder<'a> {
+    pub(crate) fn new(tree: &'a SyntaxTree, ids: &[usize]) -> Self {
+        Preorder {
+            tree,
+            stack: ids.iter().rev().copied().collect(),
+        }
+    }
+}
+
+impl<'a> Iterator for Preorder<'a> {
+    type Item = &'a Node;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while let Some(id) = self.stack.pop() {
+            if let Some(node) = self.tree.get_node(id) {
+                self.stack.extend(node.children.iter().rev());
+                return Some(node);
+            }
+        }
+        None
+    }
+}
+
+/// Iterator over the nodes of a subtree in depth-first post-order, i.e.
+/// every node comes after its children.
+pub struct Postorder<'a> {
+    events: Events<'a>,
+}
+
+impl<'a> Postorder<'a> {
+    pub(crate) fn new(tree: &'a SyntaxTree, id: usize) -> Self {
+        Postorder {
+            events: Events::new(tree, id),
+        }
+    }
+}
+
+impl<'a> Iterator for Postorder<'a> {
+    type Item = &'a Node;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.events.find_map(|event| match event {
+            Event::Leave(node) => Some(node),
+            Event::Enter(_) => None,
+        })
+    }
+}
+
+/// Iterator over the nodes of a subtree in breadth-first order, i.e. level
+/// by level.
+pub struct Bfs<'a> {
+    tree: &'a SyntaxTree,
+    queue: VecDeque<usize>,
+}
+
+impl<'a> Bfs<'a> {
+    pub(crate) fn new(tree: &'a SyntaxTree, id: usize) -> Self {
+        Bfs {
+            tree,
+            queue: VecDeque::from([id]),
+        }
+    }
+}
+
+impl<'a> Iterator for Bfs<'a> {
+    type Item = &'a Node;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while let Some(id) = self.queue.pop_front() {
+            if let Some(node) = self.tree.get_node(id) {
+                self.queue.extend(node.children.iter());
+                return Some(node);
+            }
+        }
+        None
+    }
+}
+
+/// Iterator over the ancestors of a node, from its parent up to the root.
+pub struct Ancestors<'a> {
+    tree: &'a SyntaxTree,
+    node: Option<&'a Node>,
+}
+
+impl<'a> Ancestors<'a> {
+    pub(crate) fn new(tree: &'a SyntaxTree, id: usize) -> Self {
+        Ancestors {
+            tree,
+            node: tree.get_node(id),
+        }
+    }
+}
+
+impl<'a> Iterator for Ancestors<'a> {
+    type Item = &'a Node;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let node = self.node.filter(|node| !node.is_root())?;
+        self.node = self.tree.get_node(node.parent);
+        self.node
+    }
+}
+
+/// Iterator over the [`Event`]s of a depth-first traversal of a subtree.
+pub struct Events<'a> {
+    tree: &'a SyntaxTree,
+    stack: Vec<Event<'a>>,
+}
+
+impl<'a> Events<'a> {
+    pub(crate) fn new(tree: &'a SyntaxTree, id: usize) -> Self {
+        let stack = tree.get_node(id).map(Event::Enter).into_iter().collect();
+        Events { tree, stack }
+    }
+}
+
+impl<'a> Iterator for Events<'a> {
+    type Item = Event<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let event = self.stack.pop()?;
+        if let Event::Enter(node) = event {
+            self.stack.push(Event::Leave(node));
+            let children = node.children.iter().rev();
+            let children = children.filter_map(|&id| self.tree.get_node(id));
+            self.stack.extend(children.map(Event::Enter));
+        }
+        Some(event)
+    }
+}
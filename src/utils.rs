@@ -1,22 +1,86 @@
 use proc_macro2::Span;
-use syn::{Expr, Ident, ImplItemFn, Item, ItemImpl, Lit, Pat, Path, TraitItemFn, Type};
+use syn::ext::IdentExt;
+use syn::parse::Parser;
+use syn::punctuated::Punctuated;
+use syn::visit::{self, Visit};
+use syn::{
+    Arm, Attribute, Block, Expr, ExprClosure, ExprForLoop, ExprIf, ExprLet, ExprLit, ExprPath,
+    ExprWhile, Ident, ImplItemFn, Item, ItemImpl, Lit, Local, Macro, Pat, PatIdent, Path, Token,
+    TraitItemFn, Visibility,
+};
 
+/// Returns the name of an expression: the name of the function, method,
+/// macro or variable it refers to, or a keyword for the control flow
+/// expressions (e.g. `if`).
 pub(crate) fn match_expr(expr: &Expr) -> Ident {
-    match expr {
+    let name = match expr {
+        syn::Expr::Path(p) => return match_path(&p.path).first().unwrap().to_owned(),
         syn::Expr::Call(call) => match call.func.as_ref() {
-            syn::Expr::Path(p) => match_path(&p.path).first().unwrap().to_owned(),
-            syn::Expr::Let(let_expr) => match_pat(&let_expr.pat).first().unwrap().to_owned(),
-            syn::Expr::Lit(lit_expr) => match_lit_expr(&lit_expr.lit),
-            &_ => Ident::new("Call", Span::call_site()), // TODO
+            syn::Expr::Path(_) | syn::Expr::Let(_) | syn::Expr::Lit(_) => {
+                return match_expr(&call.func)
+            }
+            _ => "Call",
         },
-        &_ => Ident::new("Expr", Span::call_site()), // TODO
-    }
+        syn::Expr::MethodCall(call) => return call.method.to_owned(),
+        syn::Expr::Macro(mac) => match match_path(&mac.mac.path).first() {
+            Some(ident) => return ident.to_owned(),
+            None => "macro",
+        },
+        syn::Expr::Let(let_expr) => return match_pat(&let_expr.pat).first().unwrap().to_owned(),
+        syn::Expr::Lit(lit_expr) => return match_lit_expr(&lit_expr.lit),
+        syn::Expr::Assign(assign) => return match_expr(&assign.left),
+        syn::Expr::Field(field) => match &field.member {
+            syn::Member::Named(ident) => return ident.to_owned(),
+            syn::Member::Unnamed(_) => "Field",
+        },
+        syn::Expr::Struct(strct) => match strct.path.segments.last() {
+            Some(segment) => return segment.ident.to_owned(),
+            None => "Struct",
+        },
+        syn::Expr::Return(ret) => match &ret.expr {
+            Some(expr) => return match_expr(expr),
+            None => "return",
+        },
+        // expressions named after the expression they wrap
+        syn::Expr::Await(expr) => return match_expr(&expr.base),
+        syn::Expr::Try(expr) => return match_expr(&expr.expr),
+        syn::Expr::Paren(expr) => return match_expr(&expr.expr),
+        syn::Expr::Group(expr) => return match_expr(&expr.expr),
+        syn::Expr::Reference(expr) => return match_expr(&expr.expr),
+        syn::Expr::RawAddr(expr) => return match_expr(&expr.expr),
+        syn::Expr::Unary(expr) => return match_expr(&expr.expr),
+        syn::Expr::Cast(expr) => return match_expr(&expr.expr),
+        syn::Expr::Index(expr) => return match_expr(&expr.expr),
+        syn::Expr::If(_) => "if",
+        syn::Expr::Match(_) => "match",
+        syn::Expr::Loop(_) => "loop",
+        syn::Expr::While(_) => "while",
+        syn::Expr::ForLoop(_) => "for",
+        syn::Expr::Break(_) => "break",
+        syn::Expr::Continue(_) => "continue",
+        syn::Expr::Async(_) => "async",
+        syn::Expr::Unsafe(_) => "unsafe",
+        syn::Expr::Const(_) => "const",
+        syn::Expr::Yield(_) => "yield",
+        syn::Expr::TryBlock(_) => "try",
+        syn::Expr::Closure(_) => "closure",
+        syn::Expr::Block(_) => "Block",
+        syn::Expr::Array(_) => "Array",
+        syn::Expr::Repeat(_) => "Repeat",
+        syn::Expr::Tuple(_) => "Tuple",
+        syn::Expr::Binary(_) => "Binary",
+        syn::Expr::Range(_) => "Range",
+        syn::Expr::Infer(_) => "Infer",
+        &_ => "Expr",
+    };
+    Ident::new(name, Span::call_site())
 }
 
+#[cfg(feature = "debug")]
 pub(crate) fn match_item(item: &Item) -> String {
     match item {
-        syn::Item::Fn(func) => format!("ItemFn: {}", func.sig.ident.to_string(),),
-        syn::Item::Struct(strct) => format!("ItemStruct: {}", strct.ident.to_string()),
+        syn::Item::Fn(func) => format!("ItemFn: {}", func.sig.ident),
+        syn::Item::Struct(strct) => format!("ItemStruct: {}", strct.ident),
         syn::Item::Impl(imp) => format!("Impl: {:?}", match_item_impl(imp)),
         syn::Item::Trait(trt) => format!("Trait: {}", trt.ident),
         syn::Item::Enum(en) => format!("Enum: {}", en.ident),
@@ -25,6 +89,31 @@ pub(crate) fn match_item(item: &Item) -> String {
     }
 }
 
+pub(crate) fn match_item_ident(item: &Item) -> Ident {
+    let name = match item {
+        syn::Item::Fn(func) => return func.sig.ident.to_owned(),
+        syn::Item::Struct(strct) => return strct.ident.to_owned(),
+        syn::Item::Trait(trt) => return trt.ident.to_owned(),
+        syn::Item::Enum(en) => return en.ident.to_owned(),
+        syn::Item::Union(un) => return un.ident.to_owned(),
+        syn::Item::Const(cnst) => return cnst.ident.to_owned(),
+        syn::Item::Static(sttc) => return sttc.ident.to_owned(),
+        syn::Item::Type(typ) => return typ.ident.to_owned(),
+        syn::Item::Mod(module) => return module.ident.to_owned(),
+        syn::Item::ExternCrate(krate) => return krate.ident.to_owned(),
+        syn::Item::TraitAlias(alias) => return alias.ident.to_owned(),
+        syn::Item::Macro(mac) => match (&mac.ident, match_path(&mac.mac.path).first()) {
+            (Some(ident), _) | (None, Some(ident)) => return ident.to_owned(),
+            (None, None) => "macro",
+        },
+        syn::Item::Impl(_) => "ItemImpl",
+        syn::Item::ForeignMod(_) => "extern",
+        syn::Item::Use(_) => "use",
+        &_ => "Item",
+    };
+    Ident::new(name, Span::call_site())
+}
+
 pub(crate) fn match_pat(pat: &Pat) -> Vec<Ident> {
     match pat {
         syn::Pat::Path(p) => p
@@ -38,18 +127,16 @@ pub(crate) fn match_pat(pat: &Pat) -> Vec<Ident> {
     }
 }
 
-pub(crate) fn match_type(typ: &Type) -> String {
+#[cfg(feature = "debug")]
+pub(crate) fn match_type(typ: &syn::Type) -> String {
     match typ {
-        Type::Path(pat) => {
-            let p = pat
-                .path
-                .segments
-                .iter()
-                .map(|p| p.ident.to_string())
-                .collect::<Vec<String>>()
-                .join(" ");
-            format!("{p}")
-        }
+        syn::Type::Path(pat) => pat
+            .path
+            .segments
+            .iter()
+            .map(|p| p.ident.to_string())
+            .collect::<Vec<String>>()
+            .join(" "),
         &_ => "syn::Type".to_string(), // TODO
     }
 }
@@ -69,22 +156,207 @@ pub(crate) fn match_item_impl(it: &ItemImpl) -> Vec<Ident> {
     }
 }
 
+/// Returns the value of a literal as an identifier, or the name of the kind
+/// of literal if the value is not a valid identifier (e.g. `42`).
 pub(crate) fn match_lit_expr(lit: &Lit) -> Ident {
-    match lit {
-        syn::Lit::Char(ch) => Ident::new(format!("{}", ch.value()).as_str(), Span::call_site()),
-        syn::Lit::Str(st) => Ident::new(format!("{}", st.value()).as_str(), Span::call_site()),
-        syn::Lit::Verbatim(verb) => Ident::new(format!("{}", verb).as_str(), Span::call_site()),
-        syn::Lit::Bool(b) => Ident::new(format!("{}", b.value()).as_str(), Span::call_site()),
-        syn::Lit::Byte(by) => Ident::new(format!("{}", by.value()).as_str(), Span::call_site()),
-        syn::Lit::ByteStr(bs) => {
-            let string = String::from_utf8(bs.value()).unwrap();
-            Ident::new(string.as_str(), Span::call_site())
+    let (value, kind) = match lit {
+        syn::Lit::Str(st) => (Some(st.value()), "LitStr"),
+        syn::Lit::ByteStr(bs) => (String::from_utf8(bs.value()).ok(), "LitByteStr"),
+        syn::Lit::Char(ch) => (Some(ch.value().to_string()), "LitChar"),
+        syn::Lit::Byte(by) => (Some(by.value().to_string()), "LitByte"),
+        syn::Lit::Bool(b) => (Some(b.value().to_string()), "LitBool"),
+        syn::Lit::Int(_) => (None, "LitInt"),
+        syn::Lit::Float(_) => (None, "LitFloat"),
+        _ => (None, "LitExpr"),
+    };
+    value
+        .and_then(|value| Ident::parse_any.parse_str(&value).ok())
+        .unwrap_or_else(|| Ident::new(kind, Span::call_site()))
+}
+
+/// Returns the name of a `match` arm: the name of the variant, struct or
+/// binding of its pattern, or `arm` for any other pattern.
+pub(crate) fn match_arm(arm: &Arm) -> Ident {
+    let path = match &arm.pat {
+        syn::Pat::Ident(pat) => return pat.ident.to_owned(),
+        syn::Pat::Path(pat) => &pat.path,
+        syn::Pat::TupleStruct(pat) => &pat.path,
+        syn::Pat::Struct(pat) => &pat.path,
+        _ => return Ident::new("arm", Span::call_site()),
+    };
+    match path.segments.last() {
+        Some(segment) => segment.ident.to_owned(),
+        None => Ident::new("arm", Span::call_site()),
+    }
+}
+
+/// Returns the variables used in the expression that are not bound in it
+/// (e.g. by the parameters of a closure or by a `let`), in order of first use.
+/// A `let` binds in the statements after it and the pattern of a closure, arm
+/// or `for` loop binds in its body only.
+pub(crate) fn free_variables(expr: &Expr) -> Vec<Ident> {
+    let mut variables = Variables::default();
+    variables.visit_expr(expr);
+    variables.free
+}
+
+/// Returns the variables bound by a pattern.
+pub(crate) fn pat_bindings(pat: &Pat) -> Vec<Ident> {
+    let mut variables = Variables::default();
+    variables.visit_pat(pat);
+    variables.scopes.pop().unwrap_or_default()
+}
+
+// the variables used in a syntax tree that are not bound in it, and the
+// variables bound in each of the scopes the visitor is in
+struct Variables {
+    free: Vec<Ident>,
+    scopes: Vec<Vec<Ident>>,
+}
+
+impl Default for Variables {
+    fn default() -> Self {
+        Variables {
+            free: vec![],
+            scopes: vec![vec![]],
+        }
+    }
+}
+
+impl Variables {
+    fn use_variable(&mut self, ident: Ident) {
+        let bound = self.scopes.iter().any(|scope| scope.contains(&ident));
+        if !bound && !self.free.contains(&ident) {
+            self.free.push(ident);
         }
-        syn::Lit::Float(fl) => Ident::new(fl.to_string().as_str(), Span::call_site()),
-        _ => Ident::new("LitExpr", Span::call_site()),
+    }
+
+    // visits the syntax tree in a new scope, the bindings are dropped
+    // afterwards
+    fn scoped(&mut self, visit: impl FnOnce(&mut Self)) {
+        self.scopes.push(vec![]);
+        visit(self);
+        self.scopes.pop();
     }
 }
 
+impl<'ast> Visit<'ast> for Variables {
+    fn visit_expr_path(&mut self, expr: &'ast ExprPath) {
+        if let (None, Some(ident)) = (&expr.qself, expr.path.get_ident()) {
+            self.use_variable(ident.to_owned());
+        }
+    }
+
+    fn visit_pat_ident(&mut self, pat: &'ast PatIdent) {
+        if let Some(scope) = self.scopes.last_mut() {
+            scope.push(pat.ident.to_owned());
+        }
+        visit::visit_pat_ident(self, pat);
+    }
+
+    fn visit_block(&mut self, block: &'ast Block) {
+        self.scoped(|variables| visit::visit_block(variables, block));
+    }
+
+    // the pattern binds after the initializer, e.g. in `let data = data.clone()`
+    fn visit_local(&mut self, local: &'ast Local) {
+        if let Some(init) = &local.init {
+            self.visit_expr(&init.expr);
+            if let Some((_, diverge)) = &init.diverge {
+                self.visit_expr(diverge);
+            }
+        }
+        self.visit_pat(&local.pat);
+    }
+
+    fn visit_expr_let(&mut self, expr: &'ast ExprLet) {
+        self.visit_expr(&expr.expr);
+        self.visit_pat(&expr.pat);
+    }
+
+    // the bindings of an `if let` are in scope in the `then` branch only
+    fn visit_expr_if(&mut self, expr: &'ast ExprIf) {
+        self.scoped(|variables| {
+            variables.visit_expr(&expr.cond);
+            variables.visit_block(&expr.then_branch);
+        });
+        if let Some((_, else_branch)) = &expr.else_branch {
+            self.visit_expr(else_branch);
+        }
+    }
+
+    fn visit_expr_while(&mut self, expr: &'ast ExprWhile) {
+        self.scoped(|variables| {
+            variables.visit_expr(&expr.cond);
+            variables.visit_block(&expr.body);
+        });
+    }
+
+    fn visit_expr_for_loop(&mut self, expr: &'ast ExprForLoop) {
+        self.visit_expr(&expr.expr);
+        self.scoped(|variables| {
+            variables.visit_pat(&expr.pat);
+            variables.visit_block(&expr.body);
+        });
+    }
+
+    fn visit_expr_closure(&mut self, expr: &'ast ExprClosure) {
+        self.scoped(|variables| {
+            for input in &expr.inputs {
+                variables.visit_pat(input);
+            }
+            variables.visit_expr(&expr.body);
+        });
+    }
+
+    fn visit_arm(&mut self, arm: &'ast Arm) {
+        self.scoped(|variables| {
+            variables.visit_pat(&arm.pat);
+            if let Some((_, guard)) = &arm.guard {
+                variables.visit_expr(guard);
+            }
+            variables.visit_expr(&arm.body);
+        });
+    }
+
+    // the arguments of macros like `println!` are usually expressions
+    fn visit_macro(&mut self, mac: &'ast Macro) {
+        let args = Punctuated::<Expr, Token![,]>::parse_terminated.parse2(mac.tokens.to_owned());
+        for arg in args.iter().flatten() {
+            match arg {
+                Expr::Lit(ExprLit {
+                    lit: Lit::Str(format),
+                    ..
+                }) => {
+                    for ident in format_variables(&format.value()) {
+                        self.use_variable(ident);
+                    }
+                }
+                arg => self.visit_expr(arg),
+            }
+        }
+    }
+}
+
+// the variables named in a format string, e.g. `x` in `"{x:?}"`
+fn format_variables(format: &str) -> Vec<Ident> {
+    let mut variables = vec![];
+    let mut rest = format;
+    while let Some(start) = rest.find('{') {
+        rest = &rest[start + 1..];
+        if let Some(escaped) = rest.strip_prefix('{') {
+            rest = escaped;
+            continue;
+        }
+        let end = rest.find(['}', ':']).unwrap_or(rest.len());
+        if let Ok(ident) = syn::parse_str::<Ident>(&rest[..end]) {
+            variables.push(ident);
+        }
+        rest = &rest[end..];
+    }
+    variables
+}
+
 pub(crate) fn match_trait_item_fn(trait_item: &TraitItemFn) -> Ident {
     trait_item.sig.ident.to_owned()
 }
@@ -92,3 +364,62 @@ pub(crate) fn match_trait_item_fn(trait_item: &TraitItemFn) -> Ident {
 pub(crate) fn match_impl_item_fn(impl_item_fn: &ImplItemFn) -> Ident {
     impl_item_fn.sig.ident.to_owned()
 }
+
+pub(crate) fn match_item_attrs(item: &Item) -> &[Attribute] {
+    match item {
+        syn::Item::Const(item) => &item.attrs,
+        syn::Item::Enum(item) => &item.attrs,
+        syn::Item::ExternCrate(item) => &item.attrs,
+        syn::Item::Fn(item) => &item.attrs,
+        syn::Item::ForeignMod(item) => &item.attrs,
+        syn::Item::Impl(item) => &item.attrs,
+        syn::Item::Macro(item) => &item.attrs,
+        syn::Item::Mod(item) => &item.attrs,
+        syn::Item::Static(item) => &item.attrs,
+        syn::Item::Struct(item) => &item.attrs,
+        syn::Item::Trait(item) => &item.attrs,
+        syn::Item::TraitAlias(item) => &item.attrs,
+        syn::Item::Type(item) => &item.attrs,
+        syn::Item::Union(item) => &item.attrs,
+        syn::Item::Use(item) => &item.attrs,
+        &_ => &[],
+    }
+}
+
+pub(crate) fn match_item_vis(item: &Item) -> Option<&Visibility> {
+    match item {
+        syn::Item::Const(item) => Some(&item.vis),
+        syn::Item::Enum(item) => Some(&item.vis),
+        syn::Item::ExternCrate(item) => Some(&item.vis),
+        syn::Item::Fn(item) => Some(&item.vis),
+        syn::Item::Mod(item) => Some(&item.vis),
+        syn::Item::Static(item) => Some(&item.vis),
+        syn::Item::Struct(item) => Some(&item.vis),
+        syn::Item::Trait(item) => Some(&item.vis),
+        syn::Item::TraitAlias(item) => Some(&item.vis),
+        syn::Item::Type(item) => Some(&item.vis),
+        syn::Item::Union(item) => Some(&item.vis),
+        syn::Item::Use(item) => Some(&item.vis),
+        &_ => None,
+    }
+}
+
+macro_rules! match_expr_attrs {
+    ($expr:expr, $($variant:ident)*) => {
+        match $expr {
+            $(
+                syn::Expr::$variant(expr) => &expr.attrs,
+            )*
+            &_ => &[],
+        }
+    };
+}
+
+pub(crate) fn match_expr_attrs(expr: &Expr) -> &[Attribute] {
+    match_expr_attrs!(
+        expr,
+        Array Assign Async Await Binary Block Break Call Cast Closure Const Continue Field
+        ForLoop Group If Index Infer Let Lit Loop Macro Match MethodCall Paren Path Range
+        RawAddr Reference Repeat Return Struct Try TryBlock Tuple Unary Unsafe While Yield
+    )
+}
@@ -0,0 +1,219 @@
+//! Visitors over the nodes of a [`SyntaxTree`].
+//!
+//! [`TreeVisitor`] and [`TreeVisitorMut`] have a method for each kind of
+//! [`Construct`], called with the node and its construct. The default
+//! implementation of every method visits the children of the node, so an
+//! implementation only overrides the methods of the constructs it is
+//! interested in and calls [`visit_children`] (or [`visit_children_mut`]) to
+//! continue the traversal below the node. The traversal is started with
+//! [`SyntaxTree::walk`] or [`SyntaxTree::walk_mut`].
+
+use crate::ast::SyntaxTree;
+use crate::construct::Construct;
+use crate::node::Node;
+use quote::ToTokens;
+use syn::{
+    Arm, Block, ExprArray, ExprAssign, ExprAsync, ExprAwait, ExprBinary, ExprBlock, ExprBreak,
+    ExprCall, ExprCast, ExprClosure, ExprConst, ExprContinue, ExprField, ExprForLoop, ExprGroup,
+    ExprIf, ExprIndex, ExprInfer, ExprLet, ExprLit, ExprLoop, ExprMacro, ExprMatch, ExprMethodCall,
+    ExprParen, ExprPath, ExprRange, ExprRawAddr, ExprReference, ExprRepeat, ExprReturn, ExprStruct,
+    ExprTry, ExprTryBlock, ExprTuple, ExprUnary, ExprUnsafe, ExprWhile, ExprYield, Field,
+    ImplItemFn, Item, ItemConst, ItemEnum, ItemFn, ItemImpl, ItemMod, ItemStatic, ItemStruct,
+    ItemTrait, ItemType, ItemUnion, ItemUse, Local, Macro, Stmt, StmtMacro, TraitItemFn, Variant,
+};
+
+// the syn type of a variant of `Construct`, given when it differs from the
+// name of the variant
+macro_rules! construct_type {
+    ($variant:ident) => {
+        $variant
+    };
+    ($variant:ident $typ:ident) => {
+        $typ
+    };
+}
+
+macro_rules! tree_visitor {
+    ($($variant:ident $(($typ:ident))? $visit:ident $visit_mut:ident)+) => {
+        /// A visitor over the nodes of a tree. The tree is available in every
+        /// method, e.g. to look up the parent or the ancestors of the node.
+        pub trait TreeVisitor<'ast> {
+            /// Visits a node of any kind. The default implementation calls
+            /// the method of the kind of construct of the node.
+            fn visit_node(&mut self, tree: &'ast SyntaxTree, node: &'ast Node) {
+                visit_node(self, tree, node)
+            }
+
+            $(
+                #[doc = concat!("Visits a node of a [`Construct::", stringify!($variant), "`].")]
+                fn $visit(
+                    &mut self,
+                    tree: &'ast SyntaxTree,
+                    node: &'ast Node,
+                    construct: &'ast construct_type!($variant $($typ)?),
+                ) {
+                    let _ = construct;
+                    visit_children(self, tree, node)
+                }
+            )+
+        }
+
+        /// A visitor that can modify the nodes of a tree. The methods get the
+        /// id of the node and a mutable reference to its construct, the rest
+        /// of the tree can be edited through the tree itself (e.g. removing
+        /// the node with [`SyntaxTree::remove_subtree`]).
+        ///
+        /// The construct argument is a copy of the construct of the node,
+        /// which takes its place when the method returns unless the node has
+        /// been removed or replaced through the tree in the meantime.
+        pub trait TreeVisitorMut {
+            /// Visits a node of any kind. The default implementation calls
+            /// the method of the kind of construct of the node.
+            fn visit_node_mut(&mut self, tree: &mut SyntaxTree, id: usize) {
+                visit_node_mut(self, tree, id)
+            }
+
+            $(
+                #[doc = concat!("Visits a node of a [`Construct::", stringify!($variant), "`].")]
+                fn $visit_mut(
+                    &mut self,
+                    tree: &mut SyntaxTree,
+                    id: usize,
+                    construct: &mut construct_type!($variant $($typ)?),
+                ) {
+                    let _ = construct;
+                    visit_children_mut(self, tree, id)
+                }
+            )+
+        }
+
+        /// Calls the method of the visitor for the kind of construct of the
+        /// node.
+        pub fn visit_node<'ast, V>(visitor: &mut V, tree: &'ast SyntaxTree, node: &'ast Node)
+        where
+            V: TreeVisitor<'ast> + ?Sized,
+        {
+            match &node.data {
+                $(
+                    Construct::$variant(construct) => visitor.$visit(tree, node, construct),
+                )+
+                _ => visit_children(visitor, tree, node),
+            }
+        }
+
+        /// Calls the method of the visitor for the kind of construct of the
+        /// node with the given id.
+        pub fn visit_node_mut<V>(visitor: &mut V, tree: &mut SyntaxTree, id: usize)
+        where
+            V: TreeVisitorMut + ?Sized,
+        {
+            let Some(node) = tree.get_node(id) else {
+                return;
+            };
+
+            let mut data = node.data.clone();
+            let original = data.to_token_stream().to_string();
+            match &mut data {
+                $(
+                    Construct::$variant(construct) => visitor.$visit_mut(tree, id, construct),
+                )+
+                _ => visit_children_mut(visitor, tree, id),
+            }
+
+            // the node may have been removed or replaced through the tree
+            if let Some(node) = tree.get_node_mut(id) {
+                if node.data.to_token_stream().to_string() == original {
+                    node.data = data;
+                }
+            }
+        }
+    };
+}
+
+tree_visitor!(
+    ItemStruct             visit_item_struct        visit_item_struct_mut
+    ItemFn                 visit_item_fn            visit_item_fn_mut
+    ItemImpl               visit_item_impl          visit_item_impl_mut
+    ItemTrait              visit_item_trait         visit_item_trait_mut
+    ItemEnum               visit_item_enum          visit_item_enum_mut
+    ItemUnion              visit_item_union         visit_item_union_mut
+    ItemConst              visit_item_const         visit_item_const_mut
+    ItemStatic             visit_item_static        visit_item_static_mut
+    ItemType               visit_item_type          visit_item_type_mut
+    ItemMod                visit_item_mod           visit_item_mod_mut
+    ItemUse                visit_item_use           visit_item_use_mut
+    Variant                visit_variant            visit_variant_mut
+    Field                  visit_field              visit_field_mut
+    TraitItemFn            visit_trait_item_fn      visit_trait_item_fn_mut
+    ImplItemFn             visit_impl_item_fn       visit_impl_item_fn_mut
+    ExprPath               visit_expr_path          visit_expr_path_mut
+    ExprMacro              visit_expr_macro         visit_expr_macro_mut
+    ExprLet                visit_expr_let           visit_expr_let_mut
+    ExprLit                visit_expr_lit           visit_expr_lit_mut
+    ExprAssign             visit_expr_assign        visit_expr_assign_mut
+    ExprReturn             visit_expr_return        visit_expr_return_mut
+    ExprArray              visit_expr_array         visit_expr_array_mut
+    ExprAsync              visit_expr_async         visit_expr_async_mut
+    ExprAwait              visit_expr_await         visit_expr_await_mut
+    ExprBinary             visit_expr_binary        visit_expr_binary_mut
+    ExprBlock              visit_expr_block         visit_expr_block_mut
+    ExprBreak              visit_expr_break         visit_expr_break_mut
+    ExprCall               visit_expr_call          visit_expr_call_mut
+    ExprCast               visit_expr_cast          visit_expr_cast_mut
+    ExprClosure            visit_expr_closure       visit_expr_closure_mut
+    ExprConst              visit_expr_const         visit_expr_const_mut
+    ExprContinue           visit_expr_continue      visit_expr_continue_mut
+    ExprField              visit_expr_field         visit_expr_field_mut
+    ExprForLoop            visit_expr_for_loop      visit_expr_for_loop_mut
+    ExprGroup              visit_expr_group         visit_expr_group_mut
+    ExprIf                 visit_expr_if            visit_expr_if_mut
+    ExprIndex              visit_expr_index         visit_expr_index_mut
+    ExprInfer              visit_expr_infer         visit_expr_infer_mut
+    ExprLoop               visit_expr_loop          visit_expr_loop_mut
+    ExprMatch              visit_expr_match         visit_expr_match_mut
+    ExprMethodCall         visit_expr_method_call   visit_expr_method_call_mut
+    ExprParen              visit_expr_paren         visit_expr_paren_mut
+    ExprRange              visit_expr_range         visit_expr_range_mut
+    ExprRawAddr            visit_expr_raw_addr      visit_expr_raw_addr_mut
+    ExprReference          visit_expr_reference     visit_expr_reference_mut
+    ExprRepeat             visit_expr_repeat        visit_expr_repeat_mut
+    ExprStruct             visit_expr_struct        visit_expr_struct_mut
+    ExprTry                visit_expr_try           visit_expr_try_mut
+    ExprTryBlock           visit_expr_try_block     visit_expr_try_block_mut
+    ExprTuple              visit_expr_tuple         visit_expr_tuple_mut
+    ExprUnary              visit_expr_unary         visit_expr_unary_mut
+    ExprUnsafe             visit_expr_unsafe        visit_expr_unsafe_mut
+    ExprWhile              visit_expr_while         visit_expr_while_mut
+    ExprYield              visit_expr_yield         visit_expr_yield_mut
+    Block                  visit_block              visit_block_mut
+    Arm                    visit_arm                visit_arm_mut
+    Method(ExprMethodCall) visit_method             visit_method_mut
+    Local                  visit_local              visit_local_mut
+    Stmt                   visit_stmt               visit_stmt_mut
+    StmtMacro              visit_stmt_macro         visit_stmt_macro_mut
+    Macro                  visit_macro              visit_macro_mut
+    Item                   visit_item               visit_item_mut
+);
+
+/// Visits the children of the node in source order.
+pub fn visit_children<'ast, V>(visitor: &mut V, tree: &'ast SyntaxTree, node: &'ast Node)
+where
+    V: TreeVisitor<'ast> + ?Sized,
+{
+    for child in tree.children(node.id) {
+        visitor.visit_node(tree, child);
+    }
+}
+
+/// Visits the children of the node with the given id in source order. The
+/// children removed or added by the visitor while visiting a sibling are
+/// skipped.
+pub fn visit_children_mut<V>(visitor: &mut V, tree: &mut SyntaxTree, id: usize)
+where
+    V: TreeVisitorMut + ?Sized,
+{
+    let children = tree.get_node(id).map(|node| node.children.to_owned());
+    for child in children.unwrap_or_default() {
+        visitor.visit_node_mut(tree, child);
+    }
+}
@@ -0,0 +1,70 @@
+use proc_macro2::Span;
+use quote::quote;
+use rustree::construct::Construct;
+use rustree::speculative_parse;
+use syn::{Ident, Visibility};
+
+#[test]
+fn should_parse_items_with_prefixes() {
+    let tokens = quote! {
+        pub(crate) fn restricted() {}
+        #[inline] fn inlined() {}
+        async fn asynchronous() {}
+        unsafe impl Send for Container {}
+        pub const fn constant() -> i32 { 0 }
+        extern "C" fn external() {}
+        #[cfg(test)] mod tests {}
+        #[derive(Debug)] pub struct Container;
+    };
+
+    let ast = speculative_parse(tokens.clone()).unwrap();
+    assert_eq!(ast.get_node(0).unwrap().get_children().len(), 8);
+
+    for name in [
+        "restricted",
+        "inlined",
+        "asynchronous",
+        "constant",
+        "external",
+    ] {
+        let node = ast.find_node(&Ident::new(name, Span::call_site())).unwrap();
+        assert!(
+            matches!(node.get_construct(), Construct::ItemFn(_)),
+            "{name}"
+        );
+    }
+
+    let inlined = ast
+        .find_node(&Ident::new("inlined", Span::call_site()))
+        .unwrap();
+    assert!(inlined.has_attribute("inline"));
+    assert!(matches!(
+        inlined.get_visibility(),
+        Some(Visibility::Inherited)
+    ));
+
+    let restricted = ast
+        .find_node(&Ident::new("restricted", Span::call_site()))
+        .unwrap();
+    assert!(restricted.get_attributes().is_empty());
+    assert!(matches!(
+        restricted.get_visibility(),
+        Some(Visibility::Restricted(_))
+    ));
+
+    let tests = ast
+        .find_node(&Ident::new("tests", Span::call_site()))
+        .unwrap();
+    assert!(tests.has_attribute("cfg"));
+
+    let container = ast
+        .find_node(&Ident::new("Container", Span::call_site()))
+        .unwrap();
+    assert!(container.has_attribute("derive"));
+    assert!(matches!(
+        container.get_visibility(),
+        Some(Visibility::Public(_))
+    ));
+
+    assert_eq!(ast.get_tokenstream().to_string(), tokens.to_string());
+}
@@ -0,0 +1,271 @@
+use quote::quote;
+use rustree::construct::ConstructKind;
+use rustree::speculative_parse;
+
+#[test]
+fn should_nest_branches_of_if() {
+    let tokens = quote! {
+        fn sign(x: i32) -> i32 {
+            if x > 0 {
+                println!("positive");
+                1
+            } else if x < 0 {
+                -1
+            } else {
+                0
+            }
+        }
+    };
+
+    let ast = speculative_parse(tokens.clone()).unwrap();
+    let expr_if = ast.find_by_kind(ConstructKind::ExprIf).next().unwrap();
+    let branches = ast
+        .children(*expr_if.get_id())
+        .map(|node| (node.get_kind(), node.get_ident().to_string()))
+        .collect::<Vec<(ConstructKind, String)>>();
+    assert_eq!(
+        branches,
+        [
+            (ConstructKind::Block, "then".to_string()),
+            (ConstructKind::ExprIf, "if".to_string())
+        ]
+    );
+
+    let then = ast.children(*expr_if.get_id()).next().unwrap();
+    assert_eq!(*then.get_level(), expr_if.get_level() + 1);
+    assert_eq!(ast.children(*then.get_id()).count(), 2);
+    assert_eq!(ast.select("if > branch[name=else]").unwrap().len(), 1);
+    assert_eq!(ast.get_tokenstream().to_string(), tokens.to_string());
+}
+
+#[test]
+fn should_nest_arms_of_match() {
+    let tokens = quote! {
+        fn describe(value: Option<i32>) -> &'static str {
+            let name = match value {
+                Some(x) if x > 0 => {
+                    count(x);
+                    "positive"
+                }
+                Some(_) => "other",
+                None => "none",
+            };
+            name
+        }
+    };
+
+    let ast = speculative_parse(tokens.clone()).unwrap();
+    let arms = ast
+        .find_by_kind(ConstructKind::Arm)
+        .map(|node| node.get_ident().to_string())
+        .collect::<Vec<String>>();
+    assert_eq!(arms, ["Some", "Some", "None"]);
+
+    let guarded = ast.select("arm[name=Some]").unwrap()[0];
+    assert_eq!(ast.children(guarded).count(), 2);
+    assert_eq!(ast.get_tokenstream().to_string(), tokens.to_string());
+}
+
+#[test]
+fn should_nest_bodies_of_loops_with_labels() {
+    let tokens = quote! {
+        fn search(grid: Grid) {
+            'outer: for row in grid {
+                while true {
+                    break 'outer;
+                }
+            }
+        }
+    };
+
+    let ast = speculative_parse(tokens.clone()).unwrap();
+    let outer = ast.select("loop[label=outer]").unwrap();
+    assert_eq!(outer.len(), 1);
+    let node = ast.get_node(outer[0]).unwrap();
+    assert_eq!(node.get_label().unwrap().ident, "outer");
+
+    let inner = ast.select("loop[label=outer] > loop > expr").unwrap();
+    assert_eq!(ast.get_node(inner[0]).unwrap().get_ident(), "break");
+    assert_eq!(ast.get_tokenstream().to_string(), tokens.to_string());
+}
+
+#[test]
+fn should_instrument_every_branch() {
+    let tokens = quote! {
+        fn check(x: i32) {
+            if x > 0 {
+                positive();
+            } else {
+                negative();
+            }
+            match x {
+                0 => zero(),
+                _ => {}
+            }
+        }
+    };
+
+    let mut ast = speculative_parse(tokens).unwrap();
+    for id in ast.select("branch, arm").unwrap() {
+        ast.insert_child(id, 0, quote! { hit(); }).unwrap();
+    }
+
+    assert_eq!(
+        ast.get_tokenstream().to_string(),
+        quote! {
+            fn check(x: i32) {
+                if x > 0 {
+                    hit();
+                    positive();
+                } else {
+                    hit();
+                    negative();
+                }
+                match x {
+                    0 => {
+                        hit();
+                        zero()
+                    },
+                    _ => {
+                        hit();
+                    }
+                }
+            }
+        }
+        .to_string()
+    );
+}
+
+#[test]
+fn should_keep_branch_names_when_replaced() {
+    let tokens = quote! {
+        fn check(x: i32) {
+            if x > 0 {
+                positive();
+            } else {
+                negative();
+            }
+        }
+    };
+
+    let mut ast = speculative_parse(tokens).unwrap();
+    let then = ast.select("branch[name=then]").unwrap()[0];
+    ast.replace_node(then, quote! { { first(); second(); } })
+        .unwrap();
+
+    let node = ast.get_node(then).unwrap();
+    assert_eq!(node.get_ident(), "then");
+    assert_eq!(ast.children(then).count(), 2);
+    assert_eq!(
+        ast.get_tokenstream().to_string(),
+        quote! {
+            fn check(x: i32) {
+                if x > 0 {
+                    first();
+                    second();
+                } else {
+                    negative();
+                }
+            }
+        }
+        .to_string()
+    );
+}
+
+#[test]
+fn should_nest_branches_inside_other_expressions() {
+    let tokens = quote! {
+        fn pick(x: u8, c: bool) -> Option<u8> {
+            let mut y = 0;
+            y = if c { 2 } else { 3 };
+            if x == 0 {
+                return Some(if x > 1 { 2 } else { 3 });
+            }
+            return match x {
+                1 => Some(2),
+                _ => None,
+            };
+        }
+    };
+
+    let mut ast = speculative_parse(tokens.clone()).unwrap();
+    let parent_kind = |ast: &rustree::ast::SyntaxTree, id: usize| {
+        let parent = *ast.get_node(id).unwrap().get_parent();
+        ast.get_node(parent).unwrap().get_kind()
+    };
+    let ifs = ast
+        .find_by_kind(ConstructKind::ExprIf)
+        .map(|node| *node.get_id())
+        .collect::<Vec<usize>>();
+    assert_eq!(ifs.len(), 3);
+    assert_eq!(parent_kind(&ast, ifs[0]), ConstructKind::ExprAssign);
+    // calls are not nodes when nested, the `if` of `Some(if ..)` is a child
+    // of the `return`
+    assert_eq!(parent_kind(&ast, ifs[2]), ConstructKind::ExprReturn);
+    let arms = ast.select("return > match > arm").unwrap();
+    assert_eq!(arms.len(), 2);
+    assert_eq!(ast.select("assign > if > branch").unwrap().len(), 2);
+    assert_eq!(ast.select("return > if > branch").unwrap().len(), 2);
+    assert_eq!(ast.get_tokenstream().to_string(), tokens.to_string());
+
+    for id in ast
+        .select("assign branch, return > if > branch, arm")
+        .unwrap()
+    {
+        ast.insert_child(id, 0, quote! { hit(); }).unwrap();
+    }
+    assert_eq!(
+        ast.get_tokenstream().to_string(),
+        quote! {
+            fn pick(x: u8, c: bool) -> Option<u8> {
+                let mut y = 0;
+                y = if c {
+                    hit();
+                    2
+                } else {
+                    hit();
+                    3
+                };
+                if x == 0 {
+                    return Some(if x > 1 {
+                        hit();
+                        2
+                    } else {
+                        hit();
+                        3
+                    });
+                }
+                return match x {
+                    1 => {
+                        hit();
+                        Some(2)
+                    },
+                    _ => {
+                        hit();
+                        None
+                    },
+                };
+            }
+        }
+        .to_string()
+    );
+}
+
+#[test]
+fn should_tell_nested_conditions_from_else_if() {
+    let tokens = quote! {
+        fn sign(x: i32) -> i32 {
+            if if x > 0 { true } else { false } {
+                1
+            } else if x < 0 {
+                -1
+            } else {
+                0
+            }
+        }
+    };
+
+    let ast = speculative_parse(tokens.clone()).unwrap();
+    assert_eq!(ast.select("fn > if > if").unwrap().len(), 2);
+    assert_eq!(ast.get_tokenstream().to_string(), tokens.to_string());
+}
@@ -0,0 +1,239 @@
+use quote::quote;
+use rustree::construct::ConstructKind;
+use rustree::error::Error;
+use rustree::speculative_parse;
+
+#[test]
+fn should_split_chains_into_receiver_and_methods() {
+    let tokens = quote! {
+        fn build(x: i32) -> Config {
+            let names = values.iter().map(|value| value.name().unwrap()).collect::<Names>();
+            Builder::new().width(x).height(x * 2).build()
+        }
+    };
+
+    let ast = speculative_parse(tokens.clone()).unwrap();
+    let build = ast.find_path("crate::build").unwrap();
+    let chain = ast.children(*build.get_id()).nth(1).unwrap();
+    assert_eq!(chain.get_kind(), ConstructKind::ExprMethodCall);
+
+    let links = ast
+        .children(*chain.get_id())
+        .map(|node| (node.get_kind(), node.get_ident().to_string()))
+        .collect::<Vec<(ConstructKind, String)>>();
+    let expected = [
+        (ConstructKind::ExprCall, "Builder"),
+        (ConstructKind::Method, "width"),
+        (ConstructKind::Method, "height"),
+        (ConstructKind::Method, "build"),
+    ];
+    assert_eq!(links, expected.map(|(kind, name)| (kind, name.to_string())));
+
+    let height = ast.select("method[name=height]").unwrap()[0];
+    assert_eq!(ast.get_node(height).unwrap().get_args().len(), 1);
+
+    let collect = ast.select("method[name=collect]").unwrap()[0];
+    let turbofish = ast.get_node(collect).unwrap().get_turbofish().unwrap();
+    assert_eq!(turbofish.args.len(), 1);
+
+    // chains nested in a closure are children of the nodes of its body
+    let unwrap = ast.select("method[name=map] closure method[name=unwrap]");
+    assert_eq!(unwrap.unwrap().len(), 1);
+
+    assert_eq!(ast.get_tokenstream().to_string(), tokens.to_string());
+}
+
+#[test]
+fn should_rewrite_every_unwrap() {
+    let tokens = quote! {
+        fn load(path: &str) -> Config {
+            let text = read(path).unwrap();
+            let config = parse(&text).unwrap();
+            validate(config.check().unwrap());
+            config
+        }
+    };
+
+    let mut ast = speculative_parse(tokens).unwrap();
+    let load = *ast.find_path("crate::load").unwrap().get_id();
+    let unwraps = ast
+        .find_in(load, |node| {
+            node.get_kind() == ConstructKind::Method && node.get_ident() == "unwrap"
+        })
+        .map(|node| *node.get_id())
+        .collect::<Vec<usize>>();
+    assert_eq!(unwraps.len(), 3);
+
+    for id in unwraps {
+        ast.replace_node(id, quote! { .expect("failed to load") })
+            .unwrap();
+    }
+    assert_eq!(
+        ast.get_tokenstream().to_string(),
+        quote! {
+            fn load(path: &str) -> Config {
+                let text = read(path).expect("failed to load");
+                let config = parse(&text).expect("failed to load");
+                validate(config.check().expect("failed to load"));
+                config
+            }
+        }
+        .to_string()
+    );
+}
+
+#[test]
+fn should_edit_methods_of_a_chain() {
+    let tokens = quote! {
+        fn main() {
+            client.connect().send();
+        }
+    };
+
+    let mut ast = speculative_parse(tokens).unwrap();
+    let chain = ast.select("fn > call").unwrap()[0];
+    ast.push_child(chain, quote! { .await }).unwrap_err();
+    ast.push_child(chain, quote! { .unwrap() }).unwrap();
+    ast.insert_child(chain, 2, quote! { .retry::<3>() })
+        .unwrap();
+    let connect = ast.select("method[name=connect]").unwrap()[0];
+    ast.remove_subtree(connect).unwrap();
+
+    assert_eq!(
+        ast.get_tokenstream().to_string(),
+        quote! {
+            fn main() {
+                client.retry::<3>().send().unwrap();
+            }
+        }
+        .to_string()
+    );
+}
+
+#[test]
+fn should_keep_chains_of_conditions_in_source_order() {
+    let tokens = quote! {
+        fn check(values: Vec<i32>) {
+            if values.is_empty() {
+                return;
+            }
+            match values.first().copied() {
+                Some(0) => {}
+                _ => {}
+            }
+        }
+    };
+
+    let ast = speculative_parse(tokens.clone()).unwrap();
+    let kinds = |kind: ConstructKind| {
+        let node = ast.find_by_kind(kind).next().unwrap();
+        ast.children(*node.get_id())
+            .map(|node| node.get_kind())
+            .collect::<Vec<ConstructKind>>()
+    };
+    assert_eq!(
+        kinds(ConstructKind::ExprIf),
+        [ConstructKind::ExprMethodCall, ConstructKind::Block]
+    );
+    assert_eq!(
+        kinds(ConstructKind::ExprMatch),
+        [
+            ConstructKind::ExprMethodCall,
+            ConstructKind::Arm,
+            ConstructKind::Arm
+        ]
+    );
+    assert_eq!(ast.get_tokenstream().to_string(), tokens.to_string());
+}
+
+#[test]
+fn should_add_chains_of_loop_heads_and_guards() {
+    let tokens = quote! {
+        fn drain(rx: Receiver, values: Vec<i32>, x: i32) {
+            while let Some(message) = rx.recv().unwrap() {
+                handle(message);
+            }
+            for value in values.iter().filter(is_even) {
+                handle(value);
+            }
+            match x {
+                x if x.checked_add(1).is_some() => {}
+                _ => {}
+            }
+        }
+    };
+
+    let mut ast = speculative_parse(tokens.clone()).unwrap();
+    for name in ["unwrap", "filter", "checked_add"] {
+        let selector = format!("method[name={name}]");
+        assert_eq!(ast.select(&selector).unwrap().len(), 1, "{name}");
+    }
+    for kind in [ConstructKind::ExprWhile, ConstructKind::ExprForLoop] {
+        let node = ast.find_by_kind(kind).next().unwrap();
+        let head = ast.children(*node.get_id()).next().unwrap();
+        assert_eq!(head.get_kind(), ConstructKind::ExprMethodCall);
+    }
+    assert_eq!(ast.get_tokenstream().to_string(), tokens.to_string());
+
+    let unwrap = ast.select("method[name=unwrap]").unwrap()[0];
+    ast.replace_node(unwrap, quote! { .expect("closed") })
+        .unwrap();
+    let body = *ast
+        .find_by_kind(ConstructKind::ExprWhile)
+        .next()
+        .unwrap()
+        .get_id();
+    ast.push_child(body, quote! { count += 1; }).unwrap();
+    let arm = ast.select("arm[name=x]").unwrap()[0];
+    ast.push_child(arm, quote! { done(); }).unwrap();
+
+    assert_eq!(
+        ast.get_tokenstream().to_string(),
+        quote! {
+            fn drain(rx: Receiver, values: Vec<i32>, x: i32) {
+                while let Some(message) = rx.recv().expect("closed") {
+                    handle(message);
+                    count += 1;
+                }
+                for value in values.iter().filter(is_even) {
+                    handle(value);
+                }
+                match x {
+                    x if x.checked_add(1).is_some() => {
+                        done();
+                    }
+                    _ => {}
+                }
+            }
+        }
+        .to_string()
+    );
+}
+
+#[test]
+fn should_keep_the_receiver_of_a_chain() {
+    let tokens = quote! {
+        fn main() {
+            a.b();
+        }
+    };
+
+    let mut ast = speculative_parse(tokens.clone()).unwrap();
+    let chain = ast.select("fn > call").unwrap()[0];
+    let receiver = *ast.children(chain).next().unwrap().get_id();
+    assert!(ast.remove_subtree(receiver).is_none());
+    let err = ast.remove_node(receiver).err().unwrap();
+    assert!(matches!(err, Error::InvalidChild { id, parent } if id == receiver && parent == chain));
+    assert_eq!(ast.get_tokenstream().to_string(), tokens.to_string());
+
+    ast.replace_node(receiver, quote! { c }).unwrap();
+    assert_eq!(
+        ast.get_tokenstream().to_string(),
+        quote! {
+            fn main() {
+                c.b();
+            }
+        }
+        .to_string()
+    );
+}
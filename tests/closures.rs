@@ -0,0 +1,174 @@
+use proc_macro2::TokenStream;
+use quote::{quote, ToTokens};
+use rustree::construct::ConstructKind;
+use rustree::speculative_parse;
+
+// prints the tokens the way syn does, e.g. `| |` for an empty closure
+fn normalize(tokens: TokenStream) -> String {
+    let file = syn::parse2::<syn::File>(tokens).unwrap();
+    file.to_token_stream().to_string()
+}
+
+#[test]
+fn should_add_closures_with_their_bodies() {
+    let tokens = quote! {
+        fn spawn_workers(config: Config) {
+            let name = config.name.clone();
+            let count = 4;
+            let handle = thread::spawn(move || {
+                let local = count * 2;
+                println!("{name} runs {}", local);
+            });
+            let doubled = values.iter().map(|x| x * count).collect::<Vec<i32>>();
+            handle.join().unwrap();
+        }
+    };
+
+    let ast = speculative_parse(tokens.clone()).unwrap();
+    let closures = ast
+        .find_by_kind(ConstructKind::ExprClosure)
+        .collect::<Vec<_>>();
+    assert_eq!(closures.len(), 2);
+
+    let spawned = closures[0];
+    assert!(spawned.is_move());
+    assert!(spawned.get_params().is_empty());
+    assert_eq!(ast.children(*spawned.get_id()).count(), 2);
+    let parent = ast.get_node(*spawned.get_parent()).unwrap();
+    assert_eq!(parent.get_ident(), "Local");
+    assert_eq!(*spawned.get_level(), parent.get_level() + 1);
+
+    let mapped = closures[1];
+    assert!(!mapped.is_move());
+    assert_eq!(mapped.get_params().len(), 1);
+    assert_eq!(ast.children(*mapped.get_id()).count(), 1);
+
+    let captures = |id: usize| {
+        let captures = ast.captures(id).into_iter();
+        captures
+            .map(|ident| ident.to_string())
+            .collect::<Vec<String>>()
+    };
+    assert_eq!(captures(*spawned.get_id()), ["count", "name"]);
+    assert_eq!(captures(*mapped.get_id()), ["count"]);
+
+    assert_eq!(normalize(ast.get_tokenstream()), normalize(tokens));
+}
+
+#[test]
+fn should_add_async_blocks() {
+    let tokens = quote! {
+        async fn serve(listener: Listener) {
+            let shared = State::new();
+            loop {
+                let socket = listener.accept().await;
+                tokio::spawn(async move {
+                    handle(socket, shared).await;
+                });
+            }
+        }
+    };
+
+    let ast = speculative_parse(tokens.clone()).unwrap();
+    let block = ast.select("async").unwrap();
+    assert_eq!(block.len(), 1);
+    let node = ast.get_node(block[0]).unwrap();
+    assert!(node.is_move());
+    assert_eq!(ast.children(block[0]).count(), 1);
+
+    let captures = ast.captures(block[0]);
+    assert_eq!(captures, ["socket", "shared"]);
+    assert_eq!(ast.get_tokenstream().to_string(), tokens.to_string());
+}
+
+#[test]
+fn should_rebuild_edited_closures() {
+    let tokens = quote! {
+        fn main() {
+            let total = 0;
+            items.for_each(|item| {
+                process(item);
+            });
+        }
+    };
+
+    let mut ast = speculative_parse(tokens).unwrap();
+    let closure = ast.select("closure").unwrap()[0];
+    ast.insert_child(closure, 0, quote! { log(total); })
+        .unwrap();
+
+    assert_eq!(ast.captures(closure), ["total"]);
+    assert_eq!(
+        ast.get_tokenstream().to_string(),
+        quote! {
+            fn main() {
+                let total = 0;
+                items.for_each(|item| {
+                    log(total);
+                    process(item);
+                });
+            }
+        }
+        .to_string()
+    );
+}
+
+#[test]
+fn should_add_closures_of_loop_heads_and_guards() {
+    let tokens = quote! {
+        fn filter(values: Vec<i32>, limit: i32) {
+            let step = 2;
+            for x in values.iter().filter(move |x| x % step == 0) {
+                print(x);
+            }
+            while check(|y| y < limit) {}
+            match limit {
+                n if test(|m| m > step) => {}
+                _ => {}
+            }
+        }
+    };
+
+    let ast = speculative_parse(tokens.clone()).unwrap();
+    let closures = ast
+        .find_by_kind(ConstructKind::ExprClosure)
+        .collect::<Vec<_>>();
+    assert_eq!(closures.len(), 3);
+    assert!(closures[0].is_move());
+
+    let captures = closures
+        .iter()
+        .map(|closure| ast.captures(*closure.get_id()))
+        .collect::<Vec<_>>();
+    assert_eq!(captures, [vec!["step"], vec![], vec!["step"]]);
+    assert_eq!(normalize(ast.get_tokenstream()), normalize(tokens));
+}
+
+#[test]
+fn should_capture_variables_used_before_they_are_shadowed() {
+    let tokens = quote! {
+        fn run(data: Vec<u8>) {
+            let a = 1;
+            let data = data;
+            let first = move || {
+                g(a);
+                let a = 2;
+                a
+            };
+            let second = move || {
+                let data = data.clone();
+                match data.first() {
+                    Some(a) => send(a),
+                    None => send(a),
+                }
+            };
+        }
+    };
+
+    let ast = speculative_parse(tokens).unwrap();
+    let closures = ast
+        .find_by_kind(ConstructKind::ExprClosure)
+        .map(|closure| ast.captures(*closure.get_id()))
+        .collect::<Vec<_>>();
+    assert_eq!(closures, [vec!["a"], vec!["data", "a"]]);
+}
@@ -0,0 +1,70 @@
+use rustree::error::Error;
+use rustree::span::Position;
+use rustree::{parse_file, parse_str};
+use std::fs;
+use std::path::Path;
+
+#[test]
+fn should_parse_str() {
+    let ast = parse_str("fn main() {\n    println!(\"hello\");\n}\n").unwrap();
+    assert!(ast.find_path("crate::main").is_some());
+}
+
+#[test]
+fn should_parse_str_with_inner_attributes() {
+    let ast = parse_str("//! doc\nfn a() {}").unwrap();
+    assert!(ast.find_path("crate::a").is_some());
+    let ast = parse_str("#![allow(dead_code)]\nfn a() {}").unwrap();
+    assert!(ast.find_path("crate::a").is_some());
+
+    let source = fs::read_to_string("src/ast.rs").unwrap();
+    let ast = parse_str(&source).unwrap();
+    assert!(ast.find_path("crate::SyntaxTree").is_some());
+}
+
+#[test]
+fn should_report_lex_error() {
+    let err = parse_str("fn main() {\n    let s = \"unterminated;\n}\n")
+        .err()
+        .unwrap();
+    assert!(matches!(err, Error::Lex { .. }), "{err}");
+}
+
+#[test]
+fn should_report_position_of_unsupported_tokens() {
+    let err = parse_str("fn main() {}\n\n  let x = 42;\n").err().unwrap();
+    let Error::Unsupported { start, end, .. } = &err else {
+        panic!("expected an unsupported construct, got {err}");
+    };
+
+    assert_eq!(
+        *start,
+        Position {
+            line: 3,
+            column: 2,
+            offset: 16
+        }
+    );
+    assert_eq!(end.line, 3);
+    assert!(err.to_string().starts_with("3:3: "), "{err}");
+}
+
+#[test]
+fn should_report_io_error() {
+    let err = parse_file("./tests/missing.rs").err().unwrap();
+    assert!(matches!(err, Error::Io { .. }));
+    assert_eq!(err.file(), Some(Path::new("./tests/missing.rs")));
+    assert!(err.start().is_none());
+}
+
+#[test]
+fn should_report_file_of_error() {
+    let dir = Path::new(env!("CARGO_TARGET_TMPDIR")).join("errors");
+    fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("broken.rs");
+    fs::write(&path, "#!/bin/sh\nstruct S;\n1 + 2\n").unwrap();
+
+    let err = parse_file(&path).err().unwrap();
+    assert_eq!(err.file(), Some(path.as_path()));
+    assert_eq!(err.start().unwrap().line, 3);
+}
@@ -0,0 +1,108 @@
+use quote::quote;
+use rustree::construct::ConstructKind;
+use rustree::speculative_parse;
+
+#[test]
+fn should_add_a_node_for_every_expression() {
+    let tokens = quote! {
+        async fn run(config: Config, values: Vec<i32>) -> Result<i32, Error> {
+            let client = Client::new();
+            client.connect(&config)?;
+            client.send().await;
+            if values.is_empty() {
+                return Ok(0);
+            }
+            match config.mode {
+                Mode::Fast => {}
+                _ => {}
+            }
+            loop {
+                break;
+            }
+            while false {}
+            for value in values {}
+            config.retries += 1;
+            config.name;
+            values[0];
+            Point { x: 1, y: 2 };
+            (1, 2);
+            [0; 4];
+            0..10;
+            -x;
+            x as u64;
+            |x: i32| x + 1;
+            unsafe {}
+            "hello world";
+            0.0;
+            Ok(config.retries)
+        }
+    };
+
+    let ast = speculative_parse(tokens.clone()).unwrap();
+    let run = ast.find_path("crate::run").unwrap();
+    let children = ast
+        .children(*run.get_id())
+        .map(|node| (node.get_kind(), node.get_ident().to_string()))
+        .collect::<Vec<(ConstructKind, String)>>();
+
+    let expected = [
+        (ConstructKind::Stmt, "Local"),
+        (ConstructKind::ExprTry, "connect"),
+        (ConstructKind::ExprAwait, "send"),
+        (ConstructKind::ExprIf, "if"),
+        (ConstructKind::ExprMatch, "match"),
+        (ConstructKind::ExprLoop, "loop"),
+        (ConstructKind::ExprWhile, "while"),
+        (ConstructKind::ExprForLoop, "for"),
+        (ConstructKind::ExprBinary, "Binary"),
+        (ConstructKind::ExprField, "name"),
+        (ConstructKind::ExprIndex, "values"),
+        (ConstructKind::ExprStruct, "Point"),
+        (ConstructKind::ExprTuple, "Tuple"),
+        (ConstructKind::ExprRepeat, "Repeat"),
+        (ConstructKind::ExprRange, "Range"),
+        (ConstructKind::ExprUnary, "x"),
+        (ConstructKind::ExprCast, "x"),
+        (ConstructKind::ExprClosure, "closure"),
+        (ConstructKind::ExprUnsafe, "unsafe"),
+        (ConstructKind::ExprLit, "LitStr"),
+        (ConstructKind::ExprLit, "LitFloat"),
+        (ConstructKind::ExprCall, "Ok"),
+    ];
+    let expected = expected.map(|(kind, ident)| (kind, ident.to_string()));
+    assert_eq!(children, expected);
+
+    assert_eq!(ast.get_tokenstream().to_string(), tokens.to_string());
+}
+
+#[test]
+fn should_select_and_replace_expressions() {
+    let tokens = quote! {
+        fn main() {
+            let value = parse(input).unwrap();
+            value.check().unwrap();
+            if value.is_empty() {
+                println!("empty");
+            }
+        }
+    };
+
+    let mut ast = speculative_parse(tokens).unwrap();
+    let calls = ast.select("fn > call[name=unwrap]").unwrap();
+    assert_eq!(calls.len(), 1);
+
+    let check = ast.select("if").unwrap()[0];
+    ast.replace_node(check, quote! { assert!(!value.is_empty()); })
+        .unwrap();
+    assert_eq!(
+        ast.get_tokenstream().to_string(),
+        quote! {
+            fn main() {
+                let value = parse(input).unwrap();
+                value.check().unwrap();
+                assert!(!value.is_empty());
+            }
+        }
+        .to_string()
+    );
+}
@@ -0,0 +1,74 @@
+use proc_macro2::Span;
+use quote::quote;
+use rustree::ast::SyntaxTree;
+use rustree::construct::ConstructKind;
+use rustree::speculative_parse;
+use syn::Ident;
+
+fn parse() -> SyntaxTree {
+    let tokens = quote! {
+        struct Point;
+        struct Line;
+
+        impl Point {
+            fn new() -> Self {
+                Point
+            }
+        }
+
+        impl Line {
+            fn new() -> Self {
+                Line
+            }
+
+            fn length(&self) -> i32 {
+                println!("length");
+                0
+            }
+        }
+    };
+    speculative_parse(tokens).unwrap()
+}
+
+#[test]
+fn should_find_all_nodes_with_name() {
+    let ast = parse();
+    let new = Ident::new("new", Span::call_site());
+    let parents = ast
+        .find_all(&new)
+        .map(|node| ast.get_node(*node.get_parent()).unwrap().get_id())
+        .collect::<Vec<_>>();
+
+    assert_eq!(parents.len(), 2);
+    assert_ne!(parents[0], parents[1]);
+}
+
+#[test]
+fn should_find_nodes_by_kind() {
+    let ast = parse();
+    let structs = ast.find_by_kind(ConstructKind::ItemStruct);
+    let names = structs.map(|node| node.get_ident().to_string());
+    assert_eq!(names.collect::<Vec<_>>(), ["Point", "Line"]);
+
+    assert_eq!(ast.find_by_kind(ConstructKind::ImplItemFn).count(), 3);
+    assert_eq!(ast.find_by_kind(ConstructKind::ItemTrait).count(), 0);
+}
+
+#[test]
+fn should_find_nodes_with_predicate() {
+    let ast = parse();
+    let leaves = ast.find_where(|node| node.is_leaf() && !node.is_root());
+    assert_eq!(leaves.count(), 6);
+
+    let line = ast
+        .find_where(|node| node.get_kind() == ConstructKind::ItemImpl)
+        .nth(1)
+        .map(|node| *node.get_id())
+        .unwrap();
+    let new = ast.find_in(line, |node| node.get_ident() == "new");
+    let new = new.map(|node| *node.get_parent()).collect::<Vec<_>>();
+    assert_eq!(new, [line]);
+
+    let macros = ast.find_in(line, |node| node.get_kind() == ConstructKind::StmtMacro);
+    assert_eq!(macros.count(), 1);
+}
@@ -0,0 +1,86 @@
+use quote::quote;
+use rustree::construct::Construct;
+use rustree::speculative_parse;
+use syn::ItemStruct;
+
+#[test]
+fn should_keep_ids_across_insertions() {
+    let tokens = quote! {
+        fn first() {
+            println!("first");
+        }
+
+        fn second() {
+            println!("second");
+        }
+    };
+
+    let mut ast = speculative_parse(tokens).unwrap();
+    let first = *ast.find_path("crate::first").unwrap().get_id();
+    let second = *ast.find_path("crate::second").unwrap().get_id();
+    let println = ast.get_node(second).unwrap().get_children()[0];
+
+    let point: ItemStruct = syn::parse_quote! { struct Point; };
+    let before = ast
+        .insert_node_before(first, Construct::ItemStruct(point.clone()))
+        .unwrap();
+    let after = ast
+        .insert_node_after(first, Construct::ItemStruct(point))
+        .unwrap();
+
+    assert_eq!(
+        ast.get_node(0).unwrap().get_children(),
+        [before, first, after, second]
+    );
+    assert_eq!(*ast.get_node(before).unwrap().get_parent(), 0);
+    assert_eq!(ast.get_node(before).unwrap().get_ident(), "Point");
+    assert_eq!(ast.get_node(second).unwrap().get_ident(), "second");
+    assert_eq!(*ast.get_node(println).unwrap().get_parent(), second);
+
+    assert_eq!(
+        ast.get_tokenstream().to_string(),
+        quote! {
+            struct Point;
+            fn first() {
+                println!("first");
+            }
+            struct Point;
+            fn second() {
+                println!("second");
+            }
+        }
+        .to_string()
+    );
+}
+
+#[test]
+fn should_keep_ids_across_removals() {
+    let tokens = quote! {
+        fn first() {}
+        fn second() {
+            println!("second");
+        }
+        fn third() {}
+    };
+
+    let mut ast = speculative_parse(tokens).unwrap();
+    let first = *ast.find_path("crate::first").unwrap().get_id();
+    let second = *ast.find_path("crate::second").unwrap().get_id();
+    let third = *ast.find_path("crate::third").unwrap().get_id();
+    let println = ast.get_node(second).unwrap().get_children()[0];
+
+    ast.remove_subtree(first).unwrap();
+    assert!(ast.get_node(first).is_none());
+    assert_eq!(ast.get_node(second).unwrap().get_ident(), "second");
+    assert_eq!(ast.get_node(third).unwrap().get_ident(), "third");
+
+    ast.remove_node(second).unwrap();
+    assert!(ast.get_node(second).is_none());
+    assert_eq!(*ast.get_node(println).unwrap().get_parent(), 0);
+    assert_eq!(ast.length(), 3);
+
+    // ids of removed nodes are never reused
+    let point = ast.push_child(0, quote! { struct Point; }).unwrap();
+    assert!(![first, second, third, println].contains(&point));
+    assert!(ast.get_node(first).is_none());
+}
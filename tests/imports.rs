@@ -0,0 +1,72 @@
+use proc_macro2::Span;
+use quote::quote;
+use rustree::construct::Construct;
+use rustree::speculative_parse;
+use syn::Ident;
+
+#[test]
+fn should_flatten_use_trees() {
+    let tokens = quote! {
+        use std::{fmt::{self, Display}, io::Write as _, collections::*};
+    };
+
+    let ast = speculative_parse(tokens.clone()).unwrap();
+    let node = ast.get_node(1).unwrap();
+    assert!(matches!(node.get_construct(), Construct::ItemUse(_)));
+
+    let imports = node.get_imports();
+    let imports = imports.iter().map(|import| import.to_string());
+    assert_eq!(
+        imports.collect::<Vec<_>>(),
+        [
+            "std::fmt",
+            "std::fmt::Display",
+            "std::io::Write as _",
+            "std::collections::*"
+        ]
+    );
+
+    let imports = node.get_imports();
+    assert_eq!(imports[0].get_name().unwrap(), "fmt");
+    assert_eq!(imports[2].get_name().unwrap(), "_");
+    assert!(imports[3].is_glob());
+    assert!(imports[3].get_name().is_none());
+
+    assert_eq!(ast.get_tokenstream().to_string(), tokens.to_string());
+}
+
+#[test]
+fn should_list_imports_in_scope() {
+    let tokens = quote! {
+        use std::io::Write;
+
+        fn write() {
+            use std::fmt::Display;
+            fn inner() {}
+        }
+
+        mod nested {
+            use std::collections::*;
+            fn read() {}
+        }
+    };
+
+    let ast = speculative_parse(tokens).unwrap();
+
+    let inner = ast
+        .find_node(&Ident::new("inner", Span::call_site()))
+        .unwrap();
+    let imports = ast.imports_in_scope(*inner.get_id());
+    let imports = imports.iter().map(|import| import.to_string());
+    assert_eq!(
+        imports.collect::<Vec<_>>(),
+        ["std::fmt::Display", "std::io::Write"]
+    );
+    assert!(ast.is_imported(*inner.get_id(), "std::io::Write"));
+
+    let read = ast
+        .find_node(&Ident::new("read", Span::call_site()))
+        .unwrap();
+    assert!(ast.is_imported(*read.get_id(), "std::collections::HashMap"));
+    assert!(!ast.is_imported(*read.get_id(), "std::io::Write"));
+}
@@ -0,0 +1,187 @@
+use quote::quote;
+use rustree::construct::Construct;
+use rustree::error::Error;
+use rustree::speculative_parse;
+
+#[test]
+fn should_insert_statement_at_start_of_function() {
+    let tokens = quote! {
+        fn first() {}
+
+        fn square(y: i32) -> i32 {
+            y * y
+        }
+    };
+
+    let mut ast = speculative_parse(tokens).unwrap();
+    let square = *ast.find_path("crate::square").unwrap().get_id();
+    let id = ast
+        .insert_child(square, 0, quote! { println!("entering square"); })
+        .unwrap();
+
+    let node = ast.get_node(id).unwrap();
+    assert_eq!(node.get_ident(), "println");
+    assert_eq!(*node.get_parent(), square);
+    assert_eq!(*node.get_level(), 2);
+    assert_eq!(ast.get_node(square).unwrap().get_children()[0], id);
+
+    assert_eq!(
+        ast.get_tokenstream().to_string(),
+        quote! {
+            fn first() {}
+
+            fn square(y: i32) -> i32 {
+                println!("entering square");
+                y * y
+            }
+        }
+        .to_string()
+    );
+}
+
+#[test]
+fn should_push_children_of_any_kind() {
+    let tokens = quote! {
+        enum Shape {
+            Circle,
+        }
+
+        impl Shape {
+            fn area(&self) -> f64 {
+                self.radius
+            }
+        }
+    };
+
+    let mut ast = speculative_parse(tokens).unwrap();
+    let shape = *ast.find_path("crate::Shape").unwrap().get_id();
+    ast.push_child(shape, quote! { Square }).unwrap();
+
+    let impl_id = ast
+        .iter()
+        .find(|node| matches!(node.get_construct(), Construct::ItemImpl(_)))
+        .map(|node| *node.get_id())
+        .unwrap();
+    let method = ast
+        .push_child(impl_id, quote! { fn name(&self) -> &str { "shape" } })
+        .unwrap();
+    assert_eq!(*ast.get_node(method).unwrap().get_level(), 2);
+
+    let area = ast
+        .iter()
+        .find(|node| node.get_ident() == "area")
+        .map(|node| *node.get_id())
+        .unwrap();
+    let stmt = ast.insert_child(area, 0, quote! { let _ = self; }).unwrap();
+    assert_eq!(*ast.get_node(stmt).unwrap().get_level(), 3);
+
+    let root = ast.push_child(0, quote! { struct Point; }).unwrap();
+    assert_eq!(*ast.get_node(root).unwrap().get_level(), 1);
+
+    assert_eq!(
+        ast.get_tokenstream().to_string(),
+        quote! {
+            enum Shape {
+                Circle,
+                Square,
+            }
+
+            impl Shape {
+                fn area(&self) -> f64 {
+                    let _ = self;
+                    self.radius
+                }
+                fn name(&self) -> &str { "shape" }
+            }
+
+            struct Point;
+        }
+        .to_string()
+    );
+}
+
+#[test]
+fn should_report_invalid_position() {
+    let mut ast = speculative_parse(quote! { fn foo() { bar(); } }).unwrap();
+
+    let err = ast.insert_child(1, 2, quote! { baz(); }).err().unwrap();
+    assert!(
+        matches!(err, Error::InvalidIndex { id: 1, index: 2 }),
+        "{err}"
+    );
+
+    let err = ast.push_child(2, quote! { baz(); }).err().unwrap();
+    assert!(matches!(err, Error::Unsupported { .. }), "{err}");
+
+    let err = ast.push_child(42, quote! { baz(); }).err().unwrap();
+    assert!(matches!(err, Error::NodeNotFound { id: 42 }), "{err}");
+}
+
+#[test]
+fn should_terminate_statements_followed_by_another() {
+    let tokens = quote! {
+        fn run(x: i32) {
+            step(x);
+        }
+    };
+
+    let mut ast = speculative_parse(tokens).unwrap();
+    let run = *ast.find_path("crate::run").unwrap().get_id();
+    ast.insert_child(run, 0, quote! { log(x) }).unwrap();
+    ast.insert_child(run, 0, quote! { trace!("run") }).unwrap();
+    ast.insert_child(run, 0, quote! { if x > 0 {} }).unwrap();
+
+    assert_eq!(
+        ast.get_tokenstream().to_string(),
+        quote! {
+            fn run(x: i32) {
+                if x > 0 {}
+                trace!("run");
+                log(x);
+                step(x);
+            }
+        }
+        .to_string()
+    );
+}
+
+#[test]
+fn should_take_back_added_semicolons() {
+    let tokens = quote! {
+        fn f() -> i32 {
+            let a = 1;
+            a
+        }
+        fn g() -> String {
+            format!("g")
+        }
+    };
+
+    let mut ast = speculative_parse(tokens.clone()).unwrap();
+    let f = *ast.find_path("crate::f").unwrap().get_id();
+    let g = *ast.find_path("crate::g").unwrap().get_id();
+    let a = ast.push_child(f, quote! { a + 1 }).unwrap();
+    let b = ast.push_child(g, quote! { String::new() }).unwrap();
+    assert_eq!(
+        ast.get_tokenstream().to_string(),
+        quote! {
+            fn f() -> i32 {
+                let a = 1;
+                a;
+                a + 1
+            }
+            fn g() -> String {
+                format!("g");
+                String::new()
+            }
+        }
+        .to_string()
+    );
+
+    ast.remove_subtree(a).unwrap();
+    ast.remove_subtree(b).unwrap();
+    assert_eq!(ast.get_tokenstream().to_string(), tokens.to_string());
+    let tail = ast.children(f).last().unwrap();
+    assert!(tail.is_tail());
+    assert!(!tail.has_semi());
+}
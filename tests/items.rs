@@ -0,0 +1,88 @@
+use proc_macro2::Span;
+use quote::quote;
+use rustree::construct::Construct;
+use rustree::speculative_parse;
+use syn::Ident;
+
+#[test]
+fn should_parse_top_level_items() {
+    let tokens = quote! {
+        enum Shape {
+            Circle(f64),
+            Square { side: f64 },
+            Empty,
+        }
+
+        union Bits {
+            int: u32,
+            float: f32,
+        }
+
+        const ORIGIN: (i32, i32) = (0, 0);
+        static COUNTER: usize = 0;
+        type Point = (i32, i32);
+
+        const fn zero() -> i32 {
+            0
+        }
+    };
+
+    let ast = speculative_parse(tokens.clone()).unwrap();
+    let root = ast.get_node(0).unwrap();
+    assert_eq!(root.get_children().len(), 6);
+
+    let shape = ast
+        .find_node(&Ident::new("Shape", Span::call_site()))
+        .unwrap();
+    assert!(matches!(shape.get_construct(), Construct::ItemEnum(_)));
+    let variants = shape
+        .get_children()
+        .iter()
+        .map(|id| ast.get_node(*id).unwrap().get_ident().to_string())
+        .collect::<Vec<_>>();
+    assert_eq!(variants, ["Circle", "Square", "Empty"]);
+
+    let bits = ast
+        .find_node(&Ident::new("Bits", Span::call_site()))
+        .unwrap();
+    assert!(matches!(bits.get_construct(), Construct::ItemUnion(_)));
+    assert_eq!(bits.get_children().len(), 2);
+
+    let kinds = ["ORIGIN", "COUNTER", "Point", "zero"].map(|name| {
+        let node = ast.find_node(&Ident::new(name, Span::call_site())).unwrap();
+        node.get_construct().clone()
+    });
+    assert!(matches!(kinds[0], Construct::ItemConst(_)));
+    assert!(matches!(kinds[1], Construct::ItemStatic(_)));
+    assert!(matches!(kinds[2], Construct::ItemType(_)));
+    assert!(matches!(kinds[3], Construct::ItemFn(_)));
+
+    assert_eq!(ast.get_tokenstream().to_string(), tokens.to_string());
+}
+
+#[test]
+fn should_rebuild_enum_from_variants() {
+    let tokens = quote! {
+        enum Shape {
+            Circle(f64),
+            Empty,
+        }
+    };
+
+    let mut ast = speculative_parse(tokens).unwrap();
+    let empty = ast
+        .find_node_mut(&Ident::new("Empty", Span::call_site()))
+        .unwrap();
+    let Construct::Variant(variant) = empty.get_construct_mut() else {
+        panic!("expected a variant");
+    };
+    variant.ident = Ident::new("Nothing", Span::call_site());
+
+    let expected = quote! {
+        enum Shape {
+            Circle(f64),
+            Nothing,
+        }
+    };
+    assert_eq!(ast.get_tokenstream().to_string(), expected.to_string());
+}
@@ -0,0 +1,48 @@
+use quote::quote;
+use rustree::construct::ConstructKind;
+use rustree::speculative_parse;
+
+#[test]
+fn should_round_trip_kind_names() {
+    for kind in ConstructKind::ALL {
+        assert_eq!(kind.to_string(), kind.name());
+        assert_eq!(kind.name().parse::<ConstructKind>().unwrap(), *kind);
+    }
+
+    assert_eq!(ConstructKind::ImplItemFn.to_string(), "impl_item_fn");
+    assert_eq!("stmt_macro".parse(), Ok(ConstructKind::StmtMacro));
+
+    let err = "ImplItemFn".parse::<ConstructKind>().err().unwrap();
+    assert_eq!(err.to_string(), "unknown construct kind `ImplItemFn`");
+}
+
+#[test]
+fn should_use_kinds_in_queries_and_statistics() {
+    let tokens = quote! {
+        fn square(y: i32) -> i32 {
+            println!("free square");
+            y * y
+        }
+
+        impl Operations for Number {
+            fn square(&self) -> i32 {
+                self.0 * self.0
+            }
+        }
+    };
+
+    let ast = speculative_parse(tokens).unwrap();
+    let node = ast.find_path("crate::square").unwrap();
+    assert_eq!(node.get_kind(), ConstructKind::ItemFn);
+    assert_eq!(node.get_construct().kind(), ConstructKind::ItemFn);
+
+    assert_eq!(ast.select("fn").unwrap().len(), 2);
+    assert_eq!(ast.select("impl_item_fn").unwrap().len(), 1);
+    assert_eq!(ast.select("item_fn > stmt_macro").unwrap().len(), 1);
+
+    let counts = ast.count_kinds();
+    assert_eq!(counts[&ConstructKind::Root], 1);
+    assert_eq!(counts[&ConstructKind::ItemFn], 1);
+    assert_eq!(counts[&ConstructKind::ImplItemFn], 1);
+    assert_eq!(counts.values().sum::<usize>(), ast.length());
+}
@@ -0,0 +1,55 @@
+use proc_macro2::Span;
+use quote::quote;
+use rustree::construct::Construct;
+use rustree::speculative_parse;
+use syn::Ident;
+
+#[test]
+fn should_parse_nested_modules() {
+    let tokens = quote! {
+        mod a {
+            pub mod b {
+                pub fn foo() {
+                    fn bar() {}
+                }
+
+                struct Baz;
+            }
+
+            mod external;
+        }
+
+        fn top() {}
+    };
+
+    let ast = speculative_parse(tokens.clone()).unwrap();
+
+    let a = ast.find_node(&Ident::new("a", Span::call_site())).unwrap();
+    assert!(matches!(a.get_construct(), Construct::ItemMod(_)));
+    assert_eq!(a.get_children().len(), 2);
+
+    let foo = ast
+        .find_node(&Ident::new("foo", Span::call_site()))
+        .unwrap();
+    let b = ast.get_node(*foo.get_parent()).unwrap();
+    assert_eq!(b.get_ident(), "b");
+    assert_eq!(*b.get_parent(), *a.get_id());
+
+    let bar = ast
+        .find_node(&Ident::new("bar", Span::call_site()))
+        .unwrap();
+    assert_eq!(ast.module_path(*bar.get_id()).unwrap(), "crate::a::b");
+    assert_eq!(ast.item_path(*foo.get_id()).unwrap(), "crate::a::b::foo");
+    assert_eq!(ast.module_path(*b.get_id()).unwrap(), "crate::a");
+
+    let top = ast
+        .find_node(&Ident::new("top", Span::call_site()))
+        .unwrap();
+    assert_eq!(ast.item_path(*top.get_id()).unwrap(), "crate::top");
+
+    let baz = ast.find_path("crate::a::b::Baz").unwrap();
+    assert!(matches!(baz.get_construct(), Construct::ItemStruct(_)));
+    assert!(ast.find_path("crate::a::b::foo::bar").is_none());
+
+    assert_eq!(ast.get_tokenstream().to_string(), tokens.to_string());
+}
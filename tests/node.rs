@@ -0,0 +1,61 @@
+use proc_macro2::Span;
+use quote::quote;
+use rustree::construct::Construct;
+use rustree::speculative_parse;
+use syn::Ident;
+
+#[test]
+fn should_expose_node_relationships() {
+    let tokens = quote! {
+        fn square(y: i32) -> i32 {
+            println!("taking the square");
+            y * y
+        }
+    };
+
+    let ast = speculative_parse(tokens).unwrap();
+    let root = ast.get_node(0).unwrap();
+    assert!(root.is_root());
+
+    let func = ast
+        .find_node(&Ident::new("square", Span::call_site()))
+        .unwrap();
+    assert!(matches!(func.get_construct(), Construct::ItemFn(_)));
+    assert_eq!(func.get_ident(), "square");
+    assert_eq!(*func.get_parent(), *root.get_id());
+    assert_eq!(func.get_children().len(), 2);
+    assert!(!func.is_leaf());
+
+    for child in func.get_children() {
+        let child = ast.get_node(*child).unwrap();
+        assert_eq!(child.get_parent(), func.get_id());
+        assert!(child.is_leaf());
+    }
+}
+
+#[test]
+fn should_emit_edited_construct() {
+    let tokens = quote! {
+        fn square(y: i32) -> i32 {
+            println!("taking the square");
+            y * y
+        }
+    };
+
+    let mut ast = speculative_parse(tokens).unwrap();
+    let node = ast
+        .find_node_mut(&Ident::new("println", Span::call_site()))
+        .unwrap();
+    let Construct::StmtMacro(mac) = node.get_construct_mut() else {
+        panic!("expected a macro statement");
+    };
+    mac.mac.tokens = quote! { "squaring {}", y };
+
+    let expected = quote! {
+        fn square(y: i32) -> i32 {
+            println!("squaring {}", y);
+            y * y
+        }
+    };
+    assert_eq!(ast.get_tokenstream().to_string(), expected.to_string());
+}
@@ -1,9 +1,82 @@
-// use rustree::parse_file;
-// use std::path::Path;
-
-// #[test]
-// fn should_parse_and_print_tree() {
-//     let path = Path::new("./tests/test_file.rs");
-//     // let arena = parse_file(path).unwrap();
-//     // arena.print_tree();
-// }
+use quote::quote;
+use rustree::construct::Construct;
+use rustree::{parse_crate, parse_file};
+use std::fs;
+use std::path::Path;
+
+#[test]
+fn should_parse_file_with_shebang_and_inner_attributes() {
+    let dir = Path::new(env!("CARGO_TARGET_TMPDIR")).join("parse_file");
+    fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("script.rs");
+    fs::write(
+        &path,
+        "#!/usr/bin/env run-cargo-script\n#![allow(dead_code)]\n\nfn main() {}\nmod util;\n",
+    )
+    .unwrap();
+
+    let ast = parse_file(&path).unwrap();
+    let root = ast.get_node(0).unwrap();
+    assert_eq!(root.get_children().len(), 2);
+    assert!(ast.find_path("crate::main").is_some());
+
+    // modules are not followed when parsing a single file
+    let util = ast.find_path("crate::util").unwrap();
+    assert!(util.is_leaf());
+
+    // the inner attributes of the file are kept in the output
+    assert_eq!(ast.get_attributes().len(), 1);
+    assert_eq!(
+        ast.get_tokenstream().to_string(),
+        quote! {
+            #![allow(dead_code)]
+            fn main() {}
+            mod util;
+        }
+        .to_string()
+    );
+}
+
+#[test]
+fn should_fail_on_missing_file() {
+    assert!(parse_file("./tests/missing.rs").is_err());
+}
+
+#[test]
+fn should_parse_crate_following_modules() {
+    let dir = Path::new(env!("CARGO_TARGET_TMPDIR")).join("parse_crate");
+    fs::create_dir_all(dir.join("src/a")).unwrap();
+    fs::create_dir_all(dir.join("src/c/d")).unwrap();
+    fs::create_dir_all(dir.join("src/other")).unwrap();
+    let files = [
+        (
+            "src/lib.rs",
+            "mod a; mod c; #[path = \"other/e.rs\"] mod e; fn root() {}",
+        ),
+        ("src/a.rs", "#![allow(unused)] pub mod b;"),
+        ("src/a/b.rs", "pub fn in_b() {}"),
+        ("src/c/mod.rs", "mod d { mod f; }"),
+        ("src/c/d/f.rs", "struct InF;"),
+        ("src/other/e.rs", "enum InE {}"),
+    ];
+    for (path, source) in files {
+        fs::write(dir.join(path), source).unwrap();
+    }
+
+    let ast = parse_crate(&dir).unwrap();
+    assert!(ast.find_path("crate::root").is_some());
+    assert!(ast.find_path("crate::a::b::in_b").is_some());
+    assert!(ast.find_path("crate::c::d::f::InF").is_some());
+    assert!(ast.find_path("crate::e::InE").is_some());
+
+    let a = ast.find_path("crate::a").unwrap();
+    assert!(matches!(a.get_construct(), Construct::ItemMod(_)));
+    assert!(a.has_attribute("allow"));
+}
+
+#[test]
+fn should_parse_this_crate() {
+    let ast = parse_crate(env!("CARGO_MANIFEST_DIR")).unwrap();
+    assert!(ast.find_path("crate::ast::SyntaxTree").is_some());
+    assert!(ast.find_path("crate::parse_crate").is_some());
+}
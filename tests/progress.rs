@@ -0,0 +1,69 @@
+use proc_macro2::TokenStream;
+use rustree::speculative_parse;
+use std::sync::mpsc;
+use std::thread;
+use std::time::Duration;
+
+// parses the input on a separate thread and fails if parsing does not
+// terminate in time
+fn parse_with_timeout(source: &'static str) -> Result<usize, String> {
+    let (sender, receiver) = mpsc::channel();
+    thread::spawn(move || {
+        let tokens: TokenStream = source.parse().unwrap();
+        let result = speculative_parse(tokens)
+            .map(|ast| ast.length())
+            .map_err(|err| err.to_string());
+        sender.send(result).unwrap();
+    });
+
+    receiver
+        .recv_timeout(Duration::from_secs(5))
+        .unwrap_or_else(|_| panic!("parsing `{source}` did not terminate"))
+}
+
+#[test]
+fn should_parse_any_item() {
+    let corpus = [
+        "use foo;",
+        "use std::{fmt, io::Write as _};",
+        "#[derive(Debug)] enum E {}",
+        "#[derive(Debug)] struct S;",
+        "extern crate alloc;",
+        "extern \"C\" { fn abs(x: i32) -> i32; }",
+        "mod m { fn f() {} }",
+        "mod m;",
+        "macro_rules! m { () => {} }",
+        "println!(\"hello\");",
+        "vec![1, 2, 3];",
+        "m! {}",
+        "unsafe impl Send for S {}",
+        "pub(crate) fn f() {}",
+        "struct A; use b::C; fn d() {}",
+    ];
+
+    for source in corpus {
+        assert!(
+            parse_with_timeout(source).is_ok(),
+            "failed to parse `{source}`"
+        );
+    }
+}
+
+#[test]
+fn should_error_on_unrecognized_tokens() {
+    let corpus = [
+        ";",
+        "1 + 2",
+        "let x = 1;",
+        "= =",
+        "fn f() {} 42",
+        "struct",
+        "impl",
+        "{ }",
+        "x.y()",
+    ];
+
+    for source in corpus {
+        assert!(parse_with_timeout(source).is_err(), "parsed `{source}`");
+    }
+}
@@ -0,0 +1,97 @@
+use quote::quote;
+use rustree::construct::Construct;
+use rustree::speculative_parse;
+
+#[test]
+fn should_emit_each_construct_once() {
+    let tokens = quote! {
+        fn cube(x: i32) -> i32 {
+            fn square(y: i32) -> i32 {
+                println!("taking the square");
+                y * y
+            }
+            let result = x * square(x);
+            print!("the cube of {x} is {result}");
+            result
+        }
+
+        trait Operations {
+            fn square(&self) -> i32 {
+                let value = { 1 + 1 };
+                value
+            }
+        }
+    };
+
+    let ast = speculative_parse(tokens.clone()).unwrap();
+    assert_eq!(ast.get_tokenstream().to_string(), tokens.to_string());
+}
+
+#[test]
+fn should_rebuild_parent_from_children() {
+    let tokens = quote! {
+        struct Container;
+
+        impl Container {
+            fn empty() {}
+        }
+    };
+
+    let mut ast = speculative_parse(tokens).unwrap();
+    let empty = ast.iter().find(|node| node.get_ident() == "empty");
+    let empty = *empty.unwrap().get_id();
+    ast.push_child(empty, quote! { fn inner() {} }).unwrap();
+    let last = ast
+        .append_tokenstream(quote! { fn last() {} }, "last")
+        .unwrap();
+    assert_eq!(*ast.get_node(last).unwrap().get_parent(), 0);
+
+    let expected = quote! {
+        struct Container;
+
+        impl Container {
+            fn empty() {
+                fn inner() {}
+            }
+        }
+
+        fn last() {}
+    };
+    assert_eq!(ast.get_tokenstream().to_string(), expected.to_string());
+}
+
+#[test]
+fn should_keep_order_of_items_and_bodies_of_added_nodes() {
+    let tokens = quote! {
+        trait Operations {
+            fn square(&self) -> u8 {
+                1
+            }
+            const ONE: u8;
+        }
+    };
+
+    let mut ast = speculative_parse(tokens.clone()).unwrap();
+    assert_eq!(ast.get_tokenstream().to_string(), tokens.to_string());
+
+    let item: syn::ItemFn = syn::parse_quote! { fn cube() { a(); b(); } };
+    let operations = *ast.find_path("crate::Operations").unwrap().get_id();
+    let cube = ast
+        .insert_node_after(operations, Construct::ItemFn(item))
+        .unwrap();
+    assert_eq!(ast.children(cube).count(), 2);
+
+    let expected = quote! {
+        trait Operations {
+            fn square(&self) -> u8 {
+                1
+            }
+            const ONE: u8;
+        }
+        fn cube() {
+            a();
+            b();
+        }
+    };
+    assert_eq!(ast.get_tokenstream().to_string(), expected.to_string());
+}
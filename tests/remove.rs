@@ -0,0 +1,170 @@
+use quote::quote;
+use rustree::ast::SyntaxTree;
+use rustree::construct::ConstructKind;
+use rustree::error::Error;
+use rustree::speculative_parse;
+
+// checks that ids refer to their nodes and that parents, children and
+// levels agree
+fn assert_consistent(ast: &SyntaxTree) {
+    for node in ast.iter() {
+        assert_eq!(
+            ast.get_node(*node.get_id()).unwrap().get_id(),
+            node.get_id()
+        );
+        for &child in node.get_children() {
+            let child = ast.get_node(child).unwrap();
+            assert_eq!(child.get_parent(), node.get_id());
+            assert_eq!(*child.get_level(), node.get_level() + 1);
+        }
+    }
+}
+
+#[test]
+fn should_remove_subtree() {
+    let tokens = quote! {
+        fn square(y: i32) -> i32 {
+            println!("taking the square");
+            y * y
+        }
+
+        #[cfg(test)]
+        mod tests {
+            fn helper() {
+                dbg!(1);
+            }
+        }
+
+        struct Point;
+    };
+
+    let mut ast = speculative_parse(tokens).unwrap();
+    let length = ast.length();
+    let tests = *ast.find_path("crate::tests").unwrap().get_id();
+    let removed = ast.remove_subtree(tests).unwrap();
+
+    assert_consistent(&ast);
+    assert_consistent(&removed);
+    assert_eq!(ast.length() + removed.length() - 1, length);
+    assert!(ast.find_path("crate::tests::helper").is_none());
+    assert!(ast.find_path("crate::Point").is_some());
+    assert_eq!(
+        ast.get_tokenstream().to_string(),
+        quote! {
+            fn square(y: i32) -> i32 {
+                println!("taking the square");
+                y * y
+            }
+
+            struct Point;
+        }
+        .to_string()
+    );
+
+    assert!(removed.find_path("crate::tests::helper").is_some());
+    assert_eq!(
+        removed.get_tokenstream().to_string(),
+        quote! {
+            #[cfg(test)]
+            mod tests {
+                fn helper() {
+                    dbg!(1);
+                }
+            }
+        }
+        .to_string()
+    );
+}
+
+#[test]
+fn should_remove_debug_statement() {
+    let tokens = quote! {
+        fn square(y: i32) -> i32 {
+            println!("taking the square");
+            y * y
+        }
+    };
+
+    let mut ast = speculative_parse(tokens).unwrap();
+    let println = ast
+        .iter()
+        .find(|node| node.get_ident() == "println")
+        .map(|node| *node.get_id())
+        .unwrap();
+    ast.remove_subtree(println).unwrap();
+
+    assert_consistent(&ast);
+    assert_eq!(
+        ast.get_tokenstream().to_string(),
+        quote! {
+            fn square(y: i32) -> i32 {
+                y * y
+            }
+        }
+        .to_string()
+    );
+}
+
+#[test]
+fn should_remove_node_and_keep_children() {
+    let tokens = quote! {
+        mod outer {
+            fn first() {}
+            fn second() {}
+        }
+        struct Point;
+    };
+
+    let mut ast = speculative_parse(tokens).unwrap();
+    let outer = *ast.find_path("crate::outer").unwrap().get_id();
+    let removed = ast.remove_node(outer).unwrap();
+
+    assert_eq!(removed.get_ident(), "outer");
+    assert!(removed.is_leaf());
+    assert_consistent(&ast);
+    assert!(ast.find_path("crate::first").is_some());
+    assert_eq!(
+        ast.get_tokenstream().to_string(),
+        quote! {
+            fn first() {}
+            fn second() {}
+            struct Point;
+        }
+        .to_string()
+    );
+}
+
+#[test]
+fn should_not_remove_root() {
+    let mut ast = speculative_parse(quote! { struct Point; }).unwrap();
+    assert!(ast.remove_subtree(0).is_none());
+    assert!(ast.remove_node(0).is_err());
+    assert!(ast.remove_subtree(42).is_none());
+}
+
+#[test]
+fn should_not_move_children_where_they_cannot_be() {
+    let tokens = quote! {
+        struct Point;
+        impl Point {
+            fn origin() -> Self {
+                Point
+            }
+        }
+        fn main() {
+            let point = Point::origin();
+        }
+    };
+
+    let mut ast = speculative_parse(tokens.clone()).unwrap();
+    let imp = ast.find_by_kind(ConstructKind::ItemImpl).next().unwrap();
+    let (imp, origin) = (*imp.get_id(), imp.get_children()[0]);
+    let err = ast.remove_node(imp).err().unwrap();
+    assert!(matches!(err, Error::InvalidChild { id, parent: 0 } if id == origin));
+
+    let main = *ast.find_path("crate::main").unwrap().get_id();
+    assert!(ast.remove_node(main).is_err());
+
+    assert_consistent(&ast);
+    assert_eq!(ast.get_tokenstream().to_string(), tokens.to_string());
+}
@@ -0,0 +1,110 @@
+use quote::quote;
+use rustree::construct::Construct;
+use rustree::error::Error;
+use rustree::speculative_parse;
+
+#[test]
+fn should_replace_function() {
+    let tokens = quote! {
+        fn foo() -> i32 {
+            println!("computing");
+            1
+        }
+
+        fn bar() {}
+    };
+
+    let mut ast = speculative_parse(tokens).unwrap();
+    let foo = *ast.find_path("crate::foo").unwrap().get_id();
+    let id = ast
+        .replace_node(foo, quote! { fn foo() -> i32 { let x = 2; x * x } })
+        .unwrap();
+
+    let node = ast.get_node(id).unwrap();
+    assert_eq!(node.get_ident(), "foo");
+    assert_eq!(node.get_children().len(), 2);
+    for &child in node.get_children() {
+        let child = ast.get_node(child).unwrap();
+        assert_eq!(child.get_parent(), node.get_id());
+        assert_eq!(*child.get_level(), 2);
+    }
+    assert!(ast.iter().all(|node| node.get_ident() != "println"));
+
+    assert_eq!(
+        ast.get_tokenstream().to_string(),
+        quote! {
+            fn foo() -> i32 { let x = 2; x * x }
+            fn bar() {}
+        }
+        .to_string()
+    );
+}
+
+#[test]
+fn should_replace_method_and_statement() {
+    let tokens = quote! {
+        impl Point {
+            fn norm(&self) -> i32 {
+                dbg!(self);
+                self.x
+            }
+        }
+    };
+
+    let mut ast = speculative_parse(tokens).unwrap();
+    let dbg = ast
+        .iter()
+        .find(|node| node.get_ident() == "dbg")
+        .map(|node| *node.get_id())
+        .unwrap();
+    ast.replace_node(dbg, quote! { let y = self.y; }).unwrap();
+
+    let norm = ast
+        .iter()
+        .find(|node| node.get_ident() == "norm")
+        .map(|node| *node.get_id())
+        .unwrap();
+    let id = ast
+        .replace_node(norm, quote! { fn norm(&self) -> i32 { self.x + self.y } })
+        .unwrap();
+    assert!(matches!(
+        ast.get_node(id).unwrap().get_construct(),
+        Construct::ImplItemFn(_)
+    ));
+
+    assert_eq!(
+        ast.get_tokenstream().to_string(),
+        quote! {
+            impl Point {
+                fn norm(&self) -> i32 { self.x + self.y }
+            }
+        }
+        .to_string()
+    );
+}
+
+#[test]
+fn should_report_invalid_replacement() {
+    let mut ast = speculative_parse(quote! { fn foo() { bar(); } }).unwrap();
+    let output = ast.get_tokenstream().to_string();
+
+    let err = ast.replace_node(1, quote! { fn foo -> }).err().unwrap();
+    assert!(matches!(err, Error::Unsupported { .. }), "{err}");
+
+    let err = ast
+        .replace_node(2, quote! { let x = 1; let y = 2; })
+        .err()
+        .unwrap();
+    assert!(matches!(err, Error::Unsupported { .. }), "{err}");
+
+    let err = ast.replace_node(42, quote! { fn foo() {} }).err().unwrap();
+    assert!(matches!(err, Error::NodeNotFound { id: 42 }), "{err}");
+
+    assert_eq!(ast.get_tokenstream().to_string(), output);
+}
+
+#[test]
+fn should_report_invalid_appended_tokens() {
+    let mut ast = speculative_parse(quote! { fn foo() {} }).unwrap();
+    assert!(ast.append_tokenstream(quote! { fn 42 }, "broken").is_err());
+}
@@ -0,0 +1,119 @@
+use quote::quote;
+use rustree::ast::SyntaxTree;
+use rustree::error::Error;
+use rustree::speculative_parse;
+
+fn parse() -> SyntaxTree {
+    let tokens = quote! {
+        fn square(y: i32) -> i32 {
+            println!("free square");
+            y * y
+        }
+
+        impl Operations for Number {
+            fn square(&self) -> i32 {
+                println!("taking the square");
+                self.0 * self.0
+            }
+
+            fn cube(&self) -> i32 {
+                println!("taking the cube");
+                self.0 * self.0 * self.0
+            }
+        }
+
+        impl std::fmt::Display for Number {
+            fn square(&self) {}
+        }
+
+        mod nested {
+            #[inline]
+            pub(crate) fn square() {
+                print!("nested square");
+            }
+        }
+    };
+    speculative_parse(tokens).unwrap()
+}
+
+fn select(ast: &SyntaxTree, selector: &str) -> Vec<String> {
+    ast.select(selector)
+        .unwrap()
+        .into_iter()
+        .map(|id| ast.item_path(id).unwrap())
+        .collect()
+}
+
+#[test]
+fn should_select_by_kind_name_and_ancestry() {
+    let ast = parse();
+    let ids = ast
+        .select("impl[trait=Operations] > fn[name=square] macro[name=println]")
+        .unwrap();
+    assert_eq!(ids.len(), 1);
+
+    let parent = ast.get_node(ids[0]).unwrap().get_parent();
+    let parent = ast.get_node(*parent).unwrap();
+    assert_eq!(parent.get_ident(), "square");
+    assert_eq!(
+        *ast.get_node(*parent.get_parent()).unwrap().get_ident(),
+        "Operations"
+    );
+
+    assert_eq!(ast.select("fn[name=square]").unwrap().len(), 4);
+    assert_eq!(ast.select("root > fn").unwrap().len(), 1);
+    assert_eq!(ast.select("impl fn macro").unwrap().len(), 2);
+    assert_eq!(ast.select("impl > macro").unwrap().len(), 0);
+    assert_eq!(
+        ast.select("macro[name=print], macro[name=println]")
+            .unwrap()
+            .len(),
+        4
+    );
+}
+
+#[test]
+fn should_prefer_short_names_over_construct_kinds() {
+    let ast = parse();
+    // `macro` is the short name of every macro, not only of `ConstructKind::Macro`
+    assert_eq!(ast.select("macro").unwrap().len(), 4);
+    assert_eq!(ast.select("stmt_macro").unwrap().len(), 4);
+    assert_eq!(ast.select("impl_item_fn").unwrap().len(), 3);
+    assert_eq!(ast.select("item_fn").unwrap().len(), 2);
+}
+
+#[test]
+fn should_select_by_properties() {
+    let ast = parse();
+    assert_eq!(
+        select(&ast, "impl[trait=std::fmt::Display]"),
+        ["crate::std"]
+    );
+    assert_eq!(select(&ast, "impl[trait=Display] > *"), ["crate::square"]);
+    assert_eq!(ast.select("impl[type=Number]").unwrap().len(), 2);
+    assert_eq!(select(&ast, "fn[attr=inline]"), ["crate::nested::square"]);
+    assert_eq!(
+        select(&ast, "mod fn[vis=\"pub(crate)\"]"),
+        ["crate::nested::square"]
+    );
+    assert_eq!(ast.select("[trait]").unwrap().len(), 2);
+}
+
+#[test]
+fn should_report_invalid_selectors() {
+    let ast = parse();
+    let offset = |selector: &str| match ast.select(selector) {
+        Err(Error::InvalidSelector { offset, .. }) => offset,
+        _ => panic!("expected an invalid selector: {selector}"),
+    };
+
+    assert_eq!(offset("impl > func"), 7);
+    assert_eq!(offset("fn[nam=square]"), 3);
+    assert_eq!(offset("fn[name=square"), 14);
+    assert_eq!(offset("fn[name=\"square]"), 8);
+    assert_eq!(offset("impl >"), 6);
+    assert_eq!(offset("fn )"), 3);
+
+    let err = ast.select("impl > func").err().unwrap();
+    assert!(err.to_string().contains("unknown kind `func`"), "{err}");
+}
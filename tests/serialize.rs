@@ -0,0 +1,72 @@
+use quote::quote;
+use rustree::ast::SyntaxTree;
+use rustree::{parse_str, speculative_parse};
+
+#[test]
+fn should_round_trip_through_json() {
+    let tokens = quote! {
+        struct Container<'a> {
+            value: &'a i32,
+        }
+
+        trait Operations {
+            fn square(&self) -> i32;
+        }
+
+        impl<'a> Operations for Container<'a> {
+            fn square(&self) -> i32 {
+                fn do_the_squaring(x: i32) -> i32 {
+                    x * x
+                }
+
+                let result = do_the_squaring(*self.value);
+                println!("the square is {result}");
+                result
+            }
+        }
+    };
+
+    let ast = speculative_parse(tokens).unwrap();
+    let json = serde_json::to_string(&ast).unwrap();
+    let restored: SyntaxTree = serde_json::from_str(&json).unwrap();
+
+    assert_eq!(restored.length(), ast.length());
+    for (node, other) in ast.iter().zip(restored.iter()) {
+        assert_eq!(node.get_id(), other.get_id());
+        assert_eq!(node.get_level(), other.get_level());
+        assert_eq!(node.get_parent(), other.get_parent());
+        assert_eq!(node.get_children(), other.get_children());
+        assert_eq!(node.get_ident(), other.get_ident());
+    }
+    assert_eq!(
+        restored.get_tokenstream().to_string(),
+        ast.get_tokenstream().to_string()
+    );
+}
+
+#[test]
+fn should_tag_constructs_with_kind_names() {
+    let ast = speculative_parse(quote! { fn square(y: i32) -> i32 { y * y } }).unwrap();
+    let json = serde_json::to_value(&ast).unwrap();
+    let kinds = json["nodes"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|node| node["data"]["kind"].as_str().unwrap())
+        .collect::<Vec<&str>>();
+
+    assert_eq!(kinds, ["root", "item_fn", "expr_binary"]);
+}
+
+#[test]
+fn should_keep_inner_attributes() {
+    let ast = parse_str("//! A crate.\n#![no_std]\nfn a() {}").unwrap();
+    let json = serde_json::to_string(&ast).unwrap();
+    let restored: SyntaxTree = serde_json::from_str(&json).unwrap();
+
+    assert_eq!(restored.get_attributes().len(), 2);
+    assert_eq!(
+        restored.get_tokenstream().to_string(),
+        ast.get_tokenstream().to_string()
+    );
+}
@@ -0,0 +1,71 @@
+use rustree::parse_str;
+use rustree::span::Position;
+
+const SOURCE: &str = "\
+struct Point {
+    x: i32,
+}
+
+fn square(x: i32) -> i32 {
+    let y = x * x;
+    y
+}
+";
+
+#[test]
+fn should_record_span_of_construct() {
+    let ast = parse_str(SOURCE).unwrap();
+    let square = ast.find_path("crate::square").unwrap();
+
+    assert_eq!(
+        square.get_start(),
+        Position {
+            line: 5,
+            column: 0,
+            offset: 30
+        }
+    );
+    assert_eq!(square.get_end().line, 8);
+    assert_eq!(square.get_end().column, 1);
+    assert_eq!(
+        &SOURCE[square.get_byte_range()],
+        "fn square(x: i32) -> i32 {\n    let y = x * x;\n    y\n}"
+    );
+}
+
+#[test]
+fn should_record_span_of_statements() {
+    let ast = parse_str(SOURCE).unwrap();
+    let local = ast.iter().find(|node| node.get_ident() == "Local").unwrap();
+
+    assert_eq!(local.get_start().line, 6);
+    assert_eq!(local.get_start().column, 4);
+    assert_eq!(&SOURCE[local.get_byte_range()], "let y = x * x;");
+}
+
+#[test]
+fn should_find_innermost_node_at_position() {
+    let ast = parse_str(SOURCE).unwrap();
+
+    let node = ast.node_at(6, 10).unwrap();
+    assert_eq!(node.get_ident(), "Local");
+    assert_eq!(*node.get_level(), 2);
+
+    let node = ast.node_at(5, 3).unwrap();
+    assert_eq!(node.get_ident(), "square");
+
+    let node = ast.node_at(2, 4).unwrap();
+    assert_eq!(node.get_ident(), "Point");
+
+    assert!(ast.node_at(4, 0).is_none());
+}
+
+#[test]
+fn should_report_error_at_node() {
+    let ast = parse_str(SOURCE).unwrap();
+    let point = ast.find_path("crate::Point").unwrap();
+    let err = point.error("unsupported struct");
+
+    assert_eq!(err.to_string(), "unsupported struct");
+    assert_eq!(err.span().start().line, 1);
+}
@@ -0,0 +1,272 @@
+use quote::quote;
+use rustree::construct::{Construct, ConstructKind};
+use rustree::speculative_parse;
+
+#[test]
+fn should_mark_tail_expressions() {
+    let tokens = quote! {
+        fn cube(x: i32) -> i32 {
+            fn square(y: i32) -> i32 {
+                println!("taking the square");
+                y * y
+            }
+            let result = x * square(x);
+            print!("the cube of {x} is {result}");
+            result
+        }
+    };
+
+    let ast = speculative_parse(tokens.clone()).unwrap();
+    let tails = ast
+        .find_where(|node| node.is_tail())
+        .map(|node| (node.get_kind(), node.get_ident().to_string()))
+        .collect::<Vec<(ConstructKind, String)>>();
+    assert_eq!(
+        tails,
+        [
+            (ConstructKind::ExprBinary, "Binary".to_string()),
+            (ConstructKind::ExprPath, "result".to_string())
+        ]
+    );
+    assert!(ast
+        .find_where(|node| node.is_tail())
+        .all(|node| !node.has_semi()));
+    assert_eq!(ast.get_tokenstream().to_string(), tokens.to_string());
+}
+
+#[test]
+fn should_mark_tails_of_branches_but_not_statements() {
+    let tokens = quote! {
+        fn check(x: i32) {
+            if x > 0 {
+                positive();
+            } else {
+                negative()
+            }
+            match x {
+                0 => zero(),
+                _ => {}
+            }
+            done();
+        }
+    };
+
+    let ast = speculative_parse(tokens).unwrap();
+    let tails = ast
+        .find_where(|node| node.is_tail())
+        .map(|node| node.get_ident().to_string())
+        .collect::<Vec<String>>();
+    assert_eq!(tails, ["negative", "zero"]);
+
+    let done = ast.find_node(&quote::format_ident!("done")).unwrap();
+    assert!(!done.is_tail());
+    assert!(done.has_semi());
+}
+
+#[test]
+fn should_list_return_points() {
+    let tokens = quote! {
+        fn parse(input: &str) -> Result<i32, Error> {
+            if input.is_empty() {
+                return Err(Error::Empty);
+            }
+            let value = match input.parse() {
+                Ok(value) => value,
+                Err(_) => return Err(Error::Invalid),
+            };
+            let check = |x: i32| {
+                return x > 0;
+            };
+            Ok(value)
+        }
+    };
+
+    let ast = speculative_parse(tokens).unwrap();
+    let parse = *ast.find_path("crate::parse").unwrap().get_id();
+    let points = ast
+        .return_points(parse)
+        .into_iter()
+        .map(|id| ast.get_node(id).unwrap())
+        .map(|node| (node.get_kind(), node.get_ident().to_string()))
+        .collect::<Vec<(ConstructKind, String)>>();
+    assert_eq!(
+        points,
+        [
+            (ConstructKind::ExprReturn, "Err".to_string()),
+            (ConstructKind::ExprReturn, "Err".to_string()),
+            (ConstructKind::ExprCall, "Ok".to_string())
+        ]
+    );
+}
+
+#[test]
+fn should_wrap_every_return_value() {
+    let tokens = quote! {
+        fn sign(x: i32) -> i32 {
+            if x < 0 {
+                return -1;
+            }
+            x.signum()
+        }
+    };
+
+    let mut ast = speculative_parse(tokens).unwrap();
+    let sign = *ast.find_path("crate::sign").unwrap().get_id();
+    for id in ast.return_points(sign) {
+        let node = ast.get_node(id).unwrap();
+        let tokens = match node.get_construct() {
+            Construct::ExprReturn(ret) => {
+                let value = ret.expr.as_ref().unwrap();
+                quote! { return traced(#value); }
+            }
+            _ => {
+                let value = node.get_construct();
+                quote! { traced(#value) }
+            }
+        };
+        ast.replace_node(id, tokens).unwrap();
+    }
+
+    let sign = ast.find_path("crate::sign").unwrap();
+    let tail = ast.children(*sign.get_id()).last().unwrap();
+    assert!(tail.is_tail());
+    assert_eq!(
+        ast.get_tokenstream().to_string(),
+        quote! {
+            fn sign(x: i32) -> i32 {
+                if x < 0 {
+                    return traced(-1);
+                }
+                traced(x.signum())
+            }
+        }
+        .to_string()
+    );
+}
+
+#[test]
+fn should_look_into_blocks_and_else_branches() {
+    let tokens = quote! {
+        fn first(v: Vec<u8>) -> u8 {
+            unsafe {
+                if v.is_empty() {
+                    return 0;
+                }
+                v.len()
+            };
+            let Some(q) = v.first() else {
+                return 1;
+            };
+            'checked: {
+                if *q > 9 {
+                    break 'checked;
+                }
+            }
+            match q {
+                0 => 2,
+                _ => 3,
+            }
+        }
+    };
+
+    let mut ast = speculative_parse(tokens.clone()).unwrap();
+    assert_eq!(ast.get_tokenstream().to_string(), tokens.to_string());
+
+    let first = *ast.find_path("crate::first").unwrap().get_id();
+    let points = ast
+        .return_points(first)
+        .into_iter()
+        .map(|id| ast.get_node(id).unwrap().get_kind())
+        .collect::<Vec<ConstructKind>>();
+    assert_eq!(
+        points,
+        [
+            ConstructKind::ExprReturn,
+            ConstructKind::ExprReturn,
+            ConstructKind::ExprMatch
+        ]
+    );
+
+    let block = *ast
+        .find_by_kind(ConstructKind::ExprUnsafe)
+        .next()
+        .unwrap()
+        .get_id();
+    ast.insert_child(block, 0, quote! { check(&v); }).unwrap();
+    let branch = ast.find_where(|node| node.get_ident() == "else").next();
+    let branch = *branch.unwrap().get_id();
+    assert_eq!(
+        ast.get_node(branch).unwrap().get_kind(),
+        ConstructKind::Block
+    );
+    ast.insert_child(branch, 0, quote! { log(); }).unwrap();
+
+    assert_eq!(
+        ast.get_tokenstream().to_string(),
+        quote! {
+            fn first(v: Vec<u8>) -> u8 {
+                unsafe {
+                    check(&v);
+                    if v.is_empty() {
+                        return 0;
+                    }
+                    v.len()
+                };
+                let Some(q) = v.first() else {
+                    log();
+                    return 1;
+                };
+                'checked: {
+                    if *q > 9 {
+                        break 'checked;
+                    }
+                }
+                match q {
+                    0 => 2,
+                    _ => 3,
+                }
+            }
+        }
+        .to_string()
+    );
+}
+
+#[test]
+fn should_move_the_tail_when_statements_change() {
+    let tokens = quote! {
+        fn f() -> i32 {
+            a();
+            x
+        }
+    };
+
+    let mut ast = speculative_parse(tokens).unwrap();
+    let f = *ast.find_path("crate::f").unwrap().get_id();
+    let x = *ast.find_node(&quote::format_ident!("x")).unwrap().get_id();
+    let done = ast.push_child(f, quote! { done(); }).unwrap();
+
+    let x_node = ast.get_node(x).unwrap();
+    assert!(!x_node.is_tail());
+    assert!(x_node.has_semi());
+    assert!(ast.return_points(f).is_empty());
+    assert_eq!(
+        ast.get_tokenstream().to_string(),
+        quote! {
+            fn f() -> i32 {
+                a();
+                x;
+                done();
+            }
+        }
+        .to_string()
+    );
+
+    let value = ast.push_child(f, quote! { value }).unwrap();
+    assert!(ast.get_node(value).unwrap().is_tail());
+    assert_eq!(ast.return_points(f), [value]);
+
+    ast.remove_subtree(value).unwrap();
+    ast.replace_node(done, quote! { done() }).unwrap();
+    assert!(ast.get_node(done).unwrap().is_tail());
+    assert_eq!(ast.return_points(f), [done]);
+}
@@ -0,0 +1,78 @@
+use quote::quote;
+use rustree::ast::SyntaxTree;
+use rustree::speculative_parse;
+use rustree::traverse::Event;
+
+fn parse() -> SyntaxTree {
+    let tokens = quote! {
+        fn cube(x: i32) -> i32 {
+            let result = x * x * x;
+            result
+        }
+
+        enum Shape {
+            Circle,
+            Square,
+        }
+    };
+    speculative_parse(tokens).unwrap()
+}
+
+fn names<'a>(nodes: impl Iterator<Item = &'a rustree::node::Node>) -> Vec<String> {
+    nodes.map(|node| node.get_ident().to_string()).collect()
+}
+
+#[test]
+fn should_traverse_in_tree_order() {
+    let mut ast = parse();
+    let cube = *ast.find_path("crate::cube").unwrap().get_id();
+    ast.insert_child(cube, 0, quote! { println!("cube"); })
+        .unwrap();
+
+    assert_eq!(
+        names(ast.preorder()),
+        ["root", "cube", "println", "Local", "result", "Shape", "Circle", "Square"]
+    );
+    assert_eq!(
+        names(ast.postorder()),
+        ["println", "Local", "result", "cube", "Circle", "Square", "Shape", "root"]
+    );
+    assert_eq!(
+        names(ast.bfs()),
+        ["root", "cube", "Shape", "println", "Local", "result", "Circle", "Square"]
+    );
+}
+
+#[test]
+fn should_follow_links_between_nodes() {
+    let ast = parse();
+    let cube = *ast.find_path("crate::cube").unwrap().get_id();
+    let local = ast.get_node(cube).unwrap().get_children()[0];
+
+    assert_eq!(names(ast.children(cube)), ["Local", "result"]);
+    assert_eq!(names(ast.descendants(0)).len(), ast.length() - 1);
+    assert_eq!(names(ast.ancestors(local)), ["cube", "root"]);
+    assert_eq!(names(ast.siblings(local)), ["result"]);
+    assert_eq!(names(ast.siblings(cube)), ["Shape"]);
+    assert_eq!(ast.siblings(0).count(), 0);
+    assert_eq!(ast.ancestors(0).count(), 0);
+    assert_eq!(ast.children(42).count(), 0);
+}
+
+#[test]
+fn should_emit_enter_and_leave_events() {
+    let ast = parse();
+    let shape = *ast.find_path("crate::Shape").unwrap().get_id();
+    let events = ast
+        .events(shape)
+        .map(|event| match event {
+            Event::Enter(node) => format!("+{}", node.get_ident()),
+            Event::Leave(node) => format!("-{}", node.get_ident()),
+        })
+        .collect::<Vec<String>>();
+
+    assert_eq!(
+        events,
+        ["+Shape", "+Circle", "-Circle", "+Square", "-Square", "-Shape"]
+    );
+}
@@ -0,0 +1,156 @@
+use quote::quote;
+use rustree::ast::SyntaxTree;
+use rustree::node::Node;
+use rustree::speculative_parse;
+use rustree::visit::{visit_children, visit_children_mut, TreeVisitor, TreeVisitorMut};
+use syn::{ImplItemFn, ItemFn, StmtMacro};
+
+fn parse() -> SyntaxTree {
+    let tokens = quote! {
+        fn cube(x: i32) -> i32 {
+            println!("taking the cube");
+            x * x * x
+        }
+
+        impl Operations for Number {
+            fn square(&self) -> i32 {
+                println!("taking the square");
+                self.0 * self.0
+            }
+        }
+    };
+    speculative_parse(tokens).unwrap()
+}
+
+// collects the macros called in methods together with the method name
+#[derive(Default)]
+struct MethodMacros {
+    macros: Vec<String>,
+}
+
+impl<'ast> TreeVisitor<'ast> for MethodMacros {
+    fn visit_stmt_macro(&mut self, tree: &'ast SyntaxTree, node: &'ast Node, mac: &'ast StmtMacro) {
+        let parent = tree.get_node(*node.get_parent()).unwrap();
+        if let rustree::construct::Construct::ImplItemFn(method) = parent.get_construct() {
+            let name = mac.mac.path.get_ident().unwrap();
+            self.macros.push(format!("{}: {name}", method.sig.ident));
+        }
+        visit_children(self, tree, node)
+    }
+}
+
+#[test]
+fn should_visit_nodes_with_parent_context() {
+    let ast = parse();
+    let mut visitor = MethodMacros::default();
+    ast.walk(&mut visitor);
+    assert_eq!(visitor.macros, ["square: println"]);
+}
+
+// renames the functions and removes the macro statements
+struct Rename;
+
+impl TreeVisitorMut for Rename {
+    fn visit_item_fn_mut(&mut self, tree: &mut SyntaxTree, id: usize, item: &mut ItemFn) {
+        item.sig.ident = quote::format_ident!("{}_renamed", item.sig.ident);
+        visit_children_mut(self, tree, id)
+    }
+
+    fn visit_impl_item_fn_mut(&mut self, tree: &mut SyntaxTree, id: usize, item: &mut ImplItemFn) {
+        item.sig.ident = quote::format_ident!("{}_renamed", item.sig.ident);
+        visit_children_mut(self, tree, id)
+    }
+
+    fn visit_stmt_macro_mut(&mut self, tree: &mut SyntaxTree, id: usize, _: &mut StmtMacro) {
+        tree.remove_subtree(id);
+    }
+}
+
+#[test]
+fn should_modify_nodes() {
+    let mut ast = parse();
+    ast.walk_mut(&mut Rename);
+
+    assert_eq!(
+        ast.get_tokenstream().to_string(),
+        quote! {
+            fn cube_renamed(x: i32) -> i32 {
+                x * x * x
+            }
+
+            impl Operations for Number {
+                fn square_renamed(&self) -> i32 {
+                    self.0 * self.0
+                }
+            }
+        }
+        .to_string()
+    );
+}
+
+// replaces the functions through the tree and records what the closures
+// capture, which needs the constructs of their ancestors
+#[derive(Default)]
+struct Replace {
+    captures: Vec<String>,
+    closures: usize,
+}
+
+impl TreeVisitorMut for Replace {
+    fn visit_item_fn_mut(&mut self, tree: &mut SyntaxTree, id: usize, item: &mut ItemFn) {
+        if item.sig.ident == "old" {
+            tree.replace_node(id, quote! { fn replaced() { b(); } })
+                .unwrap();
+            return;
+        }
+        visit_children_mut(self, tree, id)
+    }
+
+    fn visit_expr_closure_mut(
+        &mut self,
+        tree: &mut SyntaxTree,
+        id: usize,
+        _: &mut syn::ExprClosure,
+    ) {
+        let captures = tree.captures(id).into_iter();
+        self.captures
+            .extend(captures.map(|ident| ident.to_string()));
+        self.closures += tree.select("fn closure").unwrap().len();
+        visit_children_mut(self, tree, id)
+    }
+}
+
+#[test]
+fn should_see_the_tree_while_visiting() {
+    let tokens = quote! {
+        fn old() {
+            a();
+        }
+
+        fn apply() {
+            let x = 1;
+            run(move |y| x + y);
+        }
+    };
+
+    let mut ast = speculative_parse(tokens).unwrap();
+    let mut visitor = Replace::default();
+    ast.walk_mut(&mut visitor);
+
+    assert_eq!(visitor.captures, ["x"]);
+    assert_eq!(visitor.closures, 1);
+    assert_eq!(
+        ast.get_tokenstream().to_string(),
+        quote! {
+            fn replaced() {
+                b();
+            }
+
+            fn apply() {
+                let x = 1;
+                run(move |y| x + y);
+            }
+        }
+        .to_string()
+    );
+}